@@ -0,0 +1,82 @@
+use std::fmt;
+
+/// Collects all successful values or all errors
+///
+/// Unlike `collect::<Result<Vec<_>, _>>()` this doesn't stop at the first error, so that all
+/// failures can be reported at once.
+pub fn collect_errors<I, T, E>(iter: I) -> Result<Vec<T>, Vec<E>> where I: Iterator<Item = Result<T, E>> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for item in iter {
+        match item {
+            // no need to store values once we know we will fail
+            Ok(value) if errors.is_empty() => values.push(value),
+            Ok(_) => (),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Multiple errors displayed as `N errors: error; error...`
+///
+/// A single error is displayed as `1 error: error`.
+///
+/// Sources of the individual errors are not displayed.
+#[derive(Debug)]
+pub struct MultiError<E>(pub Vec<E>);
+
+impl<E> From<Vec<E>> for MultiError<E> {
+    fn from(errors: Vec<E>) -> Self {
+        MultiError(errors)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for MultiError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_error_list(f, &self.0, |f, error| write!(f, "{}", error))
+    }
+}
+
+/// Writes `N errors: error; error...` using `write_error` for each item
+///
+/// Shared by `MultiError` and `result::ErrorSummary` so that they format the same way.
+pub(crate) fn write_error_list<T, F>(f: &mut fmt::Formatter, errors: &[T], mut write_error: F) -> fmt::Result where F: FnMut(&mut fmt::Formatter, &T) -> fmt::Result {
+    match errors.len() {
+        1 => write!(f, "1 error")?,
+        count => write!(f, "{} errors", count)?,
+    }
+    let mut separator = ": ";
+    for error in errors {
+        f.write_str(separator)?;
+        write_error(f, error)?;
+        separator = "; ";
+    }
+    Ok(())
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for MultiError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_errors, MultiError};
+
+    #[test]
+    fn collect() {
+        let ok = collect_errors(vec![Ok::<_, &str>(1), Ok(2)].into_iter());
+        assert_eq!(ok, Ok(vec![1, 2]));
+        let errors = collect_errors(vec![Ok(1), Err("foo"), Ok(2), Err("bar")].into_iter()).unwrap_err();
+        assert_eq!(errors, ["foo", "bar"]);
+        assert_eq!(MultiError(errors).to_string(), "2 errors: foo; bar");
+    }
+
+    #[test]
+    fn single_error() {
+        assert_eq!(MultiError(vec!["foo"]).to_string(), "1 error: foo");
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// Reads environment variable returning an error that mentions its name
+///
+/// Unlike `std::env::var` the error message says which variable failed.
+pub fn env_var(name: &str) -> Result<String, EnvVarError> {
+    std::env::var(name).map_err(|error| EnvVarError {
+        name: name.to_owned(),
+        error,
+    })
+}
+
+/// Returned from `env_var()` when the variable is missing or is not valid UTF-8
+#[derive(Debug)]
+pub struct EnvVarError {
+    name: String,
+    error: std::env::VarError,
+}
+
+impl EnvVarError {
+    /// Name of the variable that failed to be read
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for EnvVarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.error {
+            std::env::VarError::NotPresent => write!(f, "environment variable {} not set", self.name),
+            std::env::VarError::NotUnicode(_) => write!(f, "environment variable {} contains invalid UTF-8", self.name),
+        }
+    }
+}
+
+impl std::error::Error for EnvVarError {
+    fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::env_var;
+
+    #[test]
+    fn missing() {
+        let error = env_var("RANDOM_STUFF_TEST_MISSING_VARIABLE").unwrap_err();
+        assert_eq!(error.to_string(), "environment variable RANDOM_STUFF_TEST_MISSING_VARIABLE not set");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn set() {
+        std::env::set_var("RANDOM_STUFF_TEST_SET_VARIABLE", "value");
+        assert_eq!(env_var("RANDOM_STUFF_TEST_SET_VARIABLE").unwrap(), "value");
+    }
+}
@@ -3,61 +3,361 @@ use std::fmt;
 /// Helps displaying errors
 pub trait DisplayError: std::error::Error + 'static {
     fn join_sources<'a>(&'a self, separator: &'a str) -> JoinErrorSources<'a>;
-}
 
-impl<T: std::error::Error + 'static + Sized> DisplayError for T {
-    fn join_sources<'a>(&'a self, separator: &'a str) -> JoinErrorSources<'a> {
+    /// Same as `join_sources` but displays at most `max` sources
+    ///
+    /// If the chain is longer, `... (N more)` is displayed after the last displayed source.
+    fn join_sources_limit<'a>(&'a self, separator: &'a str, max: usize) -> JoinErrorSources<'a> {
         JoinErrorSources {
-            error: self,
-            separator,
+            max_sources: max,
+            ..self.join_sources(separator)
         }
     }
+
+    /// Returns the whole chain joined by `separator` as a `String`
+    ///
+    /// Same as `join_sources(separator).to_string()`.
+    fn to_joined_string(&self, separator: &str) -> String {
+        self.join_sources(separator).to_string()
+    }
+
+    /// Writes the whole chain joined by `separator` into `writer`
+    ///
+    /// Handy when implementing `Display` of a wrapper that embeds the chain inline. The output is
+    /// the same as of `join_sources(separator)`.
+    fn write_chain<W: fmt::Write>(&self, mut writer: W, separator: &str) -> fmt::Result {
+        write_chain(&mut writer, self.join_sources(separator).error, separator, usize::MAX)
+    }
+
+    /// Joins the whole chain into a single error without sources
+    ///
+    /// The result displays the same as `join_sources(": ")` but it's a single concrete type which
+    /// can be easily transmitted as one string.
+    fn flatten_chain(&self) -> FlatError {
+        FlatError(self.join_sources(": ").to_string())
+    }
+
+    /// Displays the top error in bold red and each source dimmed on a separate line
+    ///
+    /// The sources are prefixed with `caused by: `. Colors are only used if stderr is a terminal,
+    /// see `ColorChoice::Auto`.
+    #[cfg(feature = "color")]
+    fn display_chain_colored(&self) -> ColoredChain<'_> {
+        self.display_chain_colored_with(ColorChoice::Auto)
+    }
+
+    /// Same as `display_chain_colored` but with explicit choice of using colors
+    #[cfg(feature = "color")]
+    fn display_chain_colored_with(&self, choice: ColorChoice) -> ColoredChain<'_> {
+        ColoredChain {
+            chain: self.join_sources(""),
+            colored: choice.use_colors(),
+        }
+    }
+
+    /// Returns the deepest source of the error or the error itself if it has no source
+    fn root_cause(&self) -> &(dyn std::error::Error + 'static);
+
+    /// Returns the first error in the chain (including the error itself) that is of type `E`
+    ///
+    /// Useful for branching on specific errors, e.g. `io::Error` with `ErrorKind::NotFound`. Errors
+    /// wrapped in `Box` or `Arc` (including `Arc<dyn Error>`) are found too.
+    fn find_source<E: std::error::Error + 'static>(&self) -> Option<&E>;
+}
+
+fn root_cause<'a>(mut error: &'a (dyn std::error::Error + 'static)) -> &'a (dyn std::error::Error + 'static) {
+    while let Some(source) = error.source() {
+        error = source;
+    }
+    error
 }
 
-impl DisplayError for dyn std::error::Error {
+fn find_source<'a, E: std::error::Error + 'static>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a E> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(found) = downcast_through_pointers(error) {
+            return Some(found);
+        }
+        current = error.source();
+    }
+    None
+}
+
+/// Same as `downcast_ref` but also looks inside `Box` and `Arc`
+///
+/// These forward `source()` to the inner error, so the inner error itself is never visited when
+/// walking the chain.
+fn downcast_through_pointers<'a, E: std::error::Error + 'static>(error: &'a (dyn std::error::Error + 'static)) -> Option<&'a E> {
+    use std::sync::Arc;
+
+    if let Some(found) = error.downcast_ref::<E>() {
+        return Some(found);
+    }
+    if let Some(boxed) = error.downcast_ref::<Box<E>>() {
+        return Some(boxed);
+    }
+    if let Some(arc) = error.downcast_ref::<Arc<E>>() {
+        return Some(arc);
+    }
+    let inner: &(dyn std::error::Error + 'static) = if let Some(arc) = error.downcast_ref::<Arc<dyn std::error::Error>>() {
+        &**arc
+    } else if let Some(arc) = error.downcast_ref::<Arc<dyn std::error::Error + Send>>() {
+        &**arc
+    } else if let Some(arc) = error.downcast_ref::<Arc<dyn std::error::Error + Send + Sync>>() {
+        &**arc
+    } else {
+        return None;
+    };
+    downcast_through_pointers(inner)
+}
+
+impl<T: std::error::Error + 'static + Sized> DisplayError for T {
     fn join_sources<'a>(&'a self, separator: &'a str) -> JoinErrorSources<'a> {
         JoinErrorSources {
             error: self,
             separator,
+            max_sources: usize::MAX,
         }
     }
+
+    fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+        root_cause(self)
+    }
+
+    fn find_source<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        find_source(self)
+    }
+}
+
+// `Box<dyn Error>` works through auto-deref and `Arc<dyn Error>` implements `Error` so both are
+// covered. A separate impl for `Box` is not possible because it'd conflict with the blanket impl.
+macro_rules! impl_display_error_dyn {
+    ($($type:ty),*) => {
+        $(
+            impl DisplayError for $type {
+                fn join_sources<'a>(&'a self, separator: &'a str) -> JoinErrorSources<'a> {
+                    JoinErrorSources {
+                        error: self,
+                        separator,
+                        max_sources: usize::MAX,
+                    }
+                }
+
+                fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+                    root_cause(self)
+                }
+
+                fn find_source<E: std::error::Error + 'static>(&self) -> Option<&E> {
+                    find_source(self)
+                }
+            }
+        )*
+    }
 }
 
+impl_display_error_dyn!(dyn std::error::Error, dyn std::error::Error + Send, dyn std::error::Error + Send + Sync);
+
 /// See `DisplayError::join_sources()`
 pub struct JoinErrorSources<'a> {
     error: &'a (dyn std::error::Error + 'static),
     separator: &'a str,
+    max_sources: usize,
+}
+
+/// Same as `Display`, so that `{:?}` can be used inside other `Debug` impls
+impl<'a> fmt::Debug for JoinErrorSources<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
 }
 
 impl<'a> fmt::Display for JoinErrorSources<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(self.error, f)?;
-        let mut source = self.error.source();
+        write_chain(f, self.error, self.separator, self.max_sources)
+    }
+}
+
+/// Writes the error followed by at most `max_sources` sources, each preceded by `separator`
+fn write_chain(writer: &mut dyn fmt::Write, error: &(dyn std::error::Error + 'static), separator: &str, max_sources: usize) -> fmt::Result {
+    write!(writer, "{}", error)?;
+    let mut source = error.source();
+    let mut displayed = 0;
+    while let Some(error) = source {
+        writer.write_str(separator)?;
+        if displayed == max_sources {
+            let remaining = std::iter::successors(Some(error), |error| error.source()).count();
+            return write!(writer, "... ({} more)", remaining);
+        }
+        write!(writer, "{}", error)?;
+        source = error.source();
+        displayed += 1;
+    }
+    Ok(())
+}
+
+/// Whether `DisplayError::display_chain_colored_with()` should use colors
+#[cfg(feature = "color")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Use colors if stderr is a terminal and `NO_COLOR` environment variable is not set
+    Auto,
+    Always,
+    Never,
+}
+
+#[cfg(feature = "color")]
+impl ColorChoice {
+    fn use_colors(self) -> bool {
+        use std::io::IsTerminal;
+
+        match self {
+            ColorChoice::Auto => std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && std::io::stderr().is_terminal(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// See `DisplayError::display_chain_colored()`
+#[cfg(feature = "color")]
+pub struct ColoredChain<'a> {
+    chain: JoinErrorSources<'a>,
+    colored: bool,
+}
+
+#[cfg(feature = "color")]
+impl<'a> fmt::Display for ColoredChain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use anstyle::{AnsiColor, Style};
+
+        let (top, cause) = if self.colored {
+            (Style::new().bold().fg_color(Some(AnsiColor::Red.into())), Style::new().dimmed())
+        } else {
+            (Style::new(), Style::new())
+        };
+
+        let error = self.chain.error;
+        write!(f, "{}{}{}", top.render(), error, top.render_reset())?;
+        let mut source = error.source();
         while let Some(error) = source {
-            fmt::Display::fmt(self.separator, f)?;
-            fmt::Display::fmt(error, f)?;
+            write!(f, "\n{}caused by: {}{}", cause.render(), error, cause.render_reset())?;
             source = error.source();
         }
         Ok(())
     }
 }
 
+/// Error chain pre-joined into a single string, see `DisplayError::flatten_chain()`
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{0}")]
+pub struct FlatError(String);
+
 /// Error type that should be returned from main() to display nice error messages
+///
+/// With `BoxedError` any error converts into it using `?`. If you want to avoid the allocation use
+/// a concrete error type instead and convert using `.map_err(TerminatingError::new)?`. A blanket
+/// `From` impl for concrete types is not possible because it'd conflict with the boxing one.
+///
+/// A backtrace is captured at the point of conversion and printed after the error if backtraces
+/// are enabled (`RUST_BACKTRACE=1`). Otherwise the output is unaffected.
 pub struct TerminatingError<T: TerminationInfo, E: 'static + std::error::Error> {
     _phantom: std::marker::PhantomData<T>,
     error: E,
+    backtrace: std::backtrace::Backtrace,
+}
+
+impl<T: TerminationInfo, E: 'static + std::error::Error> TerminatingError<T, E> {
+    /// Creates the error, capturing a backtrace if backtraces are enabled
+    pub fn new(error: E) -> Self {
+        TerminatingError {
+            _phantom: Default::default(),
+            error,
+            backtrace: std::backtrace::Backtrace::capture(),
+        }
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.error
+    }
+
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+
+    /// Transforms the inner error keeping the captured backtrace
+    pub fn map<E2: 'static + std::error::Error, F: FnOnce(E) -> E2>(self, f: F) -> TerminatingError<T, E2> {
+        TerminatingError {
+            _phantom: self._phantom,
+            error: f(self.error),
+            backtrace: self.backtrace,
+        }
+    }
 }
 
+/// Kept for `Result` returned from `main()`, which reports errors using `Debug`.
 impl<T: TerminationInfo, E: 'static + std::error::Error> fmt::Debug for TerminatingError<T, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        T::write_prefix(&mut *f)?;
-        std::fmt::Display::fmt(&self.error.join_sources(T::error_separator()), f)
+        T::write_error(&mut *f, &self.error)?;
+        if self.backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            T::write_backtrace(f, &self.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the error to stderr and returns the exit code provided by `TerminationInfo`
+///
+/// Unlike the `Debug` path used by `Result` returned from `main()` this doesn't print the `Error: `
+/// prefix of std and allows controlling the exit code. To use it, return `ExitCode` from `main()`
+/// and call `report()` on the error returned from your real main function.
+impl<T: TerminationInfo, E: 'static + std::error::Error> std::process::Termination for TerminatingError<T, E> {
+    fn report(self) -> std::process::ExitCode {
+        eprintln!("{:?}", self);
+        T::exit_code(&self.error).into()
+    }
+}
+
+/// Success value that can be returned from `main()` inside `Ok`
+///
+/// `Termination` is only implemented for a few types (most notably `()`), so `Result<T, E>` with
+/// arbitrary `T` can't be returned from `main()`. Wrapping the value in `Success` makes it possible.
+/// The value is dropped and the exit code is always `ExitCode::SUCCESS`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Success<T>(pub T);
+
+impl<T> std::process::Termination for Success<T> {
+    fn report(self) -> std::process::ExitCode {
+        std::process::ExitCode::SUCCESS
     }
 }
 
 pub trait TerminationInfo {
     fn write_prefix<W: std::fmt::Write>(writer: W) -> std::fmt::Result;
     fn error_separator() -> &'static str;
+
+    /// Writes the whole error
+    ///
+    /// By default writes the prefix followed by the error sources joined with the separator.
+    /// Override it if the output format doesn't fit this model.
+    fn write_error<W: std::fmt::Write>(mut writer: W, error: &(dyn 'static + std::error::Error)) -> std::fmt::Result {
+        Self::write_prefix(&mut writer)?;
+        write!(writer, "{}", error.join_sources(Self::error_separator()))
+    }
+
+    /// Writes the backtrace after the error
+    ///
+    /// Only called if the backtrace was actually captured.
+    fn write_backtrace<W: std::fmt::Write>(mut writer: W, backtrace: &std::backtrace::Backtrace) -> std::fmt::Result {
+        write!(writer, "\n\nStack backtrace:\n{}", backtrace)
+    }
+
+    /// Exit code used when reporting the error through `Termination`
+    ///
+    /// Returns 1 by default. Implementors may e.g. downcast known errors to return different codes.
+    /// This is `u8` because that's what `ExitCode` supports portably.
+    fn exit_code(_error: &(dyn 'static + std::error::Error)) -> u8 {
+        1
+    }
 }
 
 /// Prints application name and formats error sources one per line
@@ -76,7 +376,150 @@ impl TerminationInfo for MultilineTerminator {
     }
 }
 
+/// Name of the application used by `NamedTerminator`
+pub trait AppName {
+    const NAME: &'static str;
+}
+
+/// Same as `MultilineTerminator` but uses a fixed application name instead of `argv[0]`
+///
+/// Useful for daemons or when `argv[0]` is meaningless. Implement `AppName` for an empty enum
+/// (e.g. `enum MyApp {}` with `NAME = "myapp"`) and use `TerminatingError<NamedTerminator<MyApp>, _>`.
+/// The error is then printed as `Application myapp failed: ...`.
+pub struct NamedTerminator<N: AppName>(std::marker::PhantomData<N>);
+
+impl<N: AppName> TerminationInfo for NamedTerminator<N> {
+    fn write_prefix<W: std::fmt::Write>(mut writer: W) -> std::fmt::Result {
+        write!(writer, "Application {} failed: ", N::NAME)
+    }
+
+    fn error_separator() -> &'static str {
+        "\n\tcaused by: "
+    }
+}
+
+/// Formats errors like cargo does, so that editors and other tools recognize them
+///
+/// The output looks like `error: top-level error` with each source on a separate line as
+/// `  note: caused by: source`.
+pub enum CargoStyleTerminator {}
+
+impl TerminationInfo for CargoStyleTerminator {
+    fn write_prefix<W: std::fmt::Write>(mut writer: W) -> std::fmt::Result {
+        write!(writer, "error: ")
+    }
+
+    fn error_separator() -> &'static str {
+        "\n  note: caused by: "
+    }
+}
+
+/// Formats the error as JSON object for machine consumption
+///
+/// The output looks like `{"error":"top-level error","causes":["source","source of source"]}`.
+/// Prefix and separator are not used. The backtrace is never written to keep the output valid.
+///
+/// Only the `Termination::report()` path prints valid JSON. If `Result` is returned from `main()`
+/// std prints `Error: ` before the JSON, so return `ExitCode` instead:
+///
+/// ```no_run
+/// use random_stuff::error::{JsonTerminator, TerminatingError};
+/// use std::process::{ExitCode, Termination};
+///
+/// fn run() -> Result<(), TerminatingError<JsonTerminator, std::io::Error>> {
+///     std::fs::read("config.toml").map_err(TerminatingError::new)?;
+///     Ok(())
+/// }
+///
+/// fn main() -> ExitCode {
+///     match run() {
+///         Ok(()) => ExitCode::SUCCESS,
+///         Err(error) => error.report(),
+///     }
+/// }
+/// ```
+#[cfg(feature = "serde_json")]
+pub enum JsonTerminator {}
+
+#[cfg(feature = "serde_json")]
+impl TerminationInfo for JsonTerminator {
+    fn write_prefix<W: std::fmt::Write>(_writer: W) -> std::fmt::Result {
+        Ok(())
+    }
+
+    fn error_separator() -> &'static str {
+        ": "
+    }
+
+    fn write_backtrace<W: std::fmt::Write>(_writer: W, _backtrace: &std::backtrace::Backtrace) -> std::fmt::Result {
+        Ok(())
+    }
+
+    fn write_error<W: std::fmt::Write>(mut writer: W, error: &(dyn 'static + std::error::Error)) -> std::fmt::Result {
+        let mut causes = Vec::new();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        // written manually to keep the order of keys
+        let error = serde_json::to_string(&error.to_string()).map_err(|_| std::fmt::Error)?;
+        let causes = serde_json::to_string(&causes).map_err(|_| std::fmt::Error)?;
+        write!(writer, "{{\"error\":{},\"causes\":{}}}", error, causes)
+    }
+}
+
+/// Writes the full error chain into a file and a short note to stderr
+///
+/// Intended for applications launched from GUI where stderr may be invisible. Unlike
+/// `TerminationInfo` implementors this is configured at runtime, so it's used through `run()`
+/// instead of returning `TerminatingError` from `main()`.
+pub struct FileLoggingTerminator {
+    path: std::path::PathBuf,
+}
+
+impl FileLoggingTerminator {
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> Self {
+        FileLoggingTerminator {
+            path: path.into(),
+        }
+    }
+
+    /// Runs the function and reports the error if it fails
+    ///
+    /// Intended to be called from `main()` returning `ExitCode`.
+    pub fn run<E: 'static + std::error::Error, F: FnOnce() -> Result<(), E>>(&self, f: F) -> std::process::ExitCode {
+        match f() {
+            Ok(()) => std::process::ExitCode::SUCCESS,
+            Err(error) => self.report(&error, std::io::stderr()),
+        }
+    }
+
+    /// Appends the error chain to the file and writes a short message to `stderr`
+    ///
+    /// If writing to the file fails, the whole chain is written to `stderr` instead.
+    pub fn report<W: std::io::Write>(&self, error: &(dyn 'static + std::error::Error), mut stderr: W) -> std::process::ExitCode {
+        use std::io::Write;
+
+        let chain = error.join_sources("\n\tcaused by: ");
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "Error: {}", chain));
+
+        // there's nothing reasonable to do if writing to stderr fails
+        let _ = match written {
+            Ok(()) => writeln!(stderr, "Error: {} (see log at {})", error, self.path.display()),
+            Err(write_error) => writeln!(stderr, "Error: {}\nAdditionally, failed to write the error to {}: {}", chain, self.path.display(), write_error),
+        };
+        std::process::ExitCode::FAILURE
+    }
+}
+
 /// Newtype around Box<dyn std::error::Error> to implement std::error::Error.
+///
+/// This is not `Send` nor `Sync`, use `SendBoxedError` if the error needs to cross threads.
 #[derive(Debug)]
 pub struct BoxedError(Box<dyn 'static + std::error::Error>);
 
@@ -84,8 +527,131 @@ impl BoxedError {
     pub fn new<E: 'static + std::error::Error>(error: E) -> Self {
         BoxedError(Box::new(error))
     }
+
+    /// Returns the boxed error
+    pub fn into_inner(self) -> Box<dyn 'static + std::error::Error> {
+        self.0
+    }
+
+    /// Attempts to get the original error back
+    pub fn downcast_ref<E: 'static + std::error::Error>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+
+    /// Removes links that display the same message as their immediate source
+    ///
+    /// Some libraries wrap errors in transparent newtypes which just repeat the message of the
+    /// inner error. This rebuilds the chain as `ErrorSnapshot`s, skipping such redundant links.
+    pub fn dedup_chain(self) -> BoxedError {
+        let mut messages = Vec::new();
+        let mut source = Some(&*self.0);
+        while let Some(error) = source {
+            let message = error.to_string();
+            if messages.last() != Some(&message) {
+                messages.push(message);
+            }
+            source = error.source();
+        }
+
+        let snapshot = messages
+            .into_iter()
+            .rev()
+            .fold(None, |source, message| Some(ErrorSnapshot { message, source: source.map(Box::new) }))
+            .expect("the chain always contains at least one error");
+
+        BoxedError::new(snapshot)
+    }
+}
+
+/// Owned copy of an error chain
+///
+/// Stores only the messages of the original errors, so it can be kept around (or sent elsewhere)
+/// after the original error was dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorSnapshot {
+    message: String,
+    source: Option<Box<ErrorSnapshot>>,
+}
+
+impl ErrorSnapshot {
+    /// Captures messages of the error and all its sources
+    pub fn new(error: &(dyn 'static + std::error::Error)) -> Self {
+        ErrorSnapshot {
+            message: error.to_string(),
+            source: error.source().map(|source| Box::new(ErrorSnapshot::new(source))),
+        }
+    }
+}
+
+impl fmt::Display for ErrorSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.message, f)
+    }
 }
 
+impl std::error::Error for ErrorSnapshot {
+    fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
+        self.source.as_ref().map(|source| &**source as &(dyn 'static + std::error::Error))
+    }
+}
+
+impl From<Box<dyn 'static + std::error::Error>> for BoxedError {
+    fn from(error: Box<dyn 'static + std::error::Error>) -> Self {
+        BoxedError(error)
+    }
+}
+
+impl From<String> for BoxedError {
+    fn from(message: String) -> Self {
+        BoxedError::new(StringError(message))
+    }
+}
+
+impl<'a> From<&'a str> for BoxedError {
+    fn from(message: &'a str) -> Self {
+        BoxedError::new(StringError(message.to_owned()))
+    }
+}
+
+/// Error adding context to another error, see `IntoResult::context()`
+///
+/// Displays only the context, the original error is returned from `source()`.
+#[derive(Debug)]
+pub struct ContextError<C: fmt::Display + fmt::Debug> {
+    context: C,
+    source: Box<dyn 'static + std::error::Error>,
+}
+
+impl<C: fmt::Display + fmt::Debug> ContextError<C> {
+    pub fn new<E: 'static + std::error::Error>(context: C, source: E) -> Self {
+        ContextError {
+            context,
+            source: Box::new(source),
+        }
+    }
+
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+}
+
+impl<C: fmt::Display + fmt::Debug> fmt::Display for ContextError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.context, f)
+    }
+}
+
+impl<C: fmt::Display + fmt::Debug> std::error::Error for ContextError<C> {
+    fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
+        Some(&*self.source)
+    }
+}
+
+/// Ad-hoc error containing just a message
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{0}")]
+pub struct StringError(pub String);
+
 impl fmt::Display for BoxedError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(&*self.0, f)
@@ -96,13 +662,390 @@ impl std::error::Error for BoxedError {
     fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
         self.0.source()
     }
+
+    /// Forwards to the inner error so that the provided context survives boxing
+    #[cfg(feature = "nightly")]
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        self.0.provide(request)
+    }
 }
 
 impl<T, E> From<E> for TerminatingError<T, BoxedError> where T: TerminationInfo, E: 'static + std::error::Error {
     fn from(value: E) -> Self {
-        TerminatingError {
-            _phantom: Default::default(),
-            error: BoxedError::new(value),
+        TerminatingError::new(BoxedError::new(value))
+    }
+}
+
+/// Same as `BoxedError` but `Send + Sync`
+///
+/// Use this when errors are sent through channels or returned from spawned threads. It only
+/// accepts errors that are `Send + Sync` so prefer `BoxedError` in single-threaded code.
+#[derive(Debug)]
+pub struct SendBoxedError(Box<dyn 'static + std::error::Error + Send + Sync>);
+
+impl SendBoxedError {
+    pub fn new<E: 'static + std::error::Error + Send + Sync>(error: E) -> Self {
+        SendBoxedError(Box::new(error))
+    }
+
+    /// Returns the boxed error
+    pub fn into_inner(self) -> Box<dyn 'static + std::error::Error + Send + Sync> {
+        self.0
+    }
+
+    /// Attempts to get the original error back
+    pub fn downcast_ref<E: 'static + std::error::Error>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+impl From<Box<dyn 'static + std::error::Error + Send + Sync>> for SendBoxedError {
+    fn from(error: Box<dyn 'static + std::error::Error + Send + Sync>) -> Self {
+        SendBoxedError(error)
+    }
+}
+
+impl From<String> for SendBoxedError {
+    fn from(message: String) -> Self {
+        SendBoxedError::new(StringError(message))
+    }
+}
+
+impl<'a> From<&'a str> for SendBoxedError {
+    fn from(message: &'a str) -> Self {
+        SendBoxedError::new(StringError(message.to_owned()))
+    }
+}
+
+impl From<SendBoxedError> for BoxedError {
+    fn from(error: SendBoxedError) -> Self {
+        BoxedError(error.0)
+    }
+}
+
+impl fmt::Display for SendBoxedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl std::error::Error for SendBoxedError {
+    fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
+        self.0.source()
+    }
+
+    /// Forwards to the inner error so that the provided context survives boxing
+    #[cfg(feature = "nightly")]
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        self.0.provide(request)
+    }
+}
+
+impl<T, E> From<E> for TerminatingError<T, SendBoxedError> where T: TerminationInfo, E: 'static + std::error::Error + Send + Sync {
+    fn from(value: E) -> Self {
+        TerminatingError::new(SendBoxedError::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoxedError, DisplayError};
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct ChainError {
+        message: &'static str,
+        source: Option<Box<ChainError>>,
+    }
+
+    impl ChainError {
+        fn new(messages: &[&'static str]) -> Self {
+            let (message, rest) = messages.split_first().unwrap();
+            ChainError {
+                message,
+                source: if rest.is_empty() { None } else { Some(Box::new(ChainError::new(rest))) },
+            }
+        }
+    }
+
+    impl fmt::Display for ChainError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.message)
         }
     }
+
+    impl std::error::Error for ChainError {
+        fn source(&self) -> Option<&(dyn 'static + std::error::Error)> {
+            self.source.as_ref().map(|source| &**source as &(dyn 'static + std::error::Error))
+        }
+    }
+
+    #[test]
+    fn flatten_chain() {
+        let error = ChainError::new(&["foo", "bar", "baz"]);
+        let flat = error.flatten_chain();
+        assert_eq!(flat.to_string(), error.join_sources(": ").to_string());
+        assert!(std::error::Error::source(&flat).is_none());
+    }
+
+    #[test]
+    fn downcast() {
+        let error = BoxedError::new(ChainError::new(&["foo"]));
+        assert_eq!(error.downcast_ref::<ChainError>().unwrap().message, "foo");
+        assert!(error.downcast_ref::<std::io::Error>().is_none());
+        assert!(error.into_inner().downcast::<ChainError>().is_ok());
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_terminator() {
+        use super::{JsonTerminator, TerminatingError};
+
+        let error = TerminatingError::<JsonTerminator, BoxedError>::from(ChainError::new(&["foo \"x\"", "bar", "baz"]));
+        assert_eq!(format!("{:?}", error), r#"{"error":"foo \"x\"","causes":["bar","baz"]}"#);
+
+        let json: serde_json::Value = serde_json::from_str(&format!("{:?}", error)).unwrap();
+        assert_eq!(json["error"], "foo \"x\"");
+        assert_eq!(json["causes"], serde_json::json!(["bar", "baz"]));
+    }
+
+    #[test]
+    fn file_logging_terminator() {
+        let path = std::env::temp_dir().join(format!("random_stuff_test_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let terminator = super::FileLoggingTerminator::new(&path);
+        let mut stderr = Vec::new();
+        terminator.report(&ChainError::new(&["foo", "bar"]), &mut stderr);
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(log, "Error: foo\n\tcaused by: bar\n");
+        assert_eq!(String::from_utf8(stderr).unwrap(), format!("Error: foo (see log at {})\n", path.display()));
+    }
+
+    #[test]
+    fn join_sources_debug() {
+        let error = ChainError::new(&["foo", "bar"]);
+        assert_eq!(format!("{:?}", error.join_sources(": ")), "foo: bar");
+    }
+
+    #[test]
+    fn root_cause() {
+        assert_eq!(ChainError::new(&["foo"]).root_cause().to_string(), "foo");
+        assert_eq!(ChainError::new(&["foo", "bar"]).root_cause().to_string(), "bar");
+        let error: Box<dyn std::error::Error> = Box::new(ChainError::new(&["foo", "bar", "baz"]));
+        assert_eq!(error.root_cause().to_string(), "baz");
+    }
+
+    #[test]
+    fn boxed_from_string() {
+        let error = BoxedError::from("something went wrong");
+        assert_eq!(error.to_string(), "something went wrong");
+        assert!(std::error::Error::source(&error).is_none());
+        assert_eq!(BoxedError::from(String::from("foo")).to_string(), "foo");
+    }
+
+    #[test]
+    fn typed_terminating_error() {
+        use super::{MultilineTerminator, TerminatingError};
+
+        fn fallible() -> Result<(), ChainError> {
+            Err(ChainError::new(&["foo", "bar"]))
+        }
+
+        fn run() -> Result<(), TerminatingError<MultilineTerminator, ChainError>> {
+            fallible().map_err(TerminatingError::new)?;
+            Ok(())
+        }
+
+        let message = format!("{:?}", run().unwrap_err());
+        assert!(message.contains("failed: foo\n\tcaused by: bar"));
+    }
+
+    #[test]
+    fn join_sources_limit() {
+        let error = ChainError::new(&["foo", "bar", "baz"]);
+        assert_eq!(error.join_sources_limit(": ", 3).to_string(), "foo: bar: baz");
+        assert_eq!(error.join_sources_limit(": ", 2).to_string(), "foo: bar: baz");
+        assert_eq!(error.join_sources_limit(": ", 1).to_string(), "foo: bar: ... (1 more)");
+        assert_eq!(error.join_sources_limit(": ", 0).to_string(), "foo: ... (2 more)");
+    }
+
+    #[test]
+    fn dedup_chain() {
+        let error = BoxedError::new(ChainError::new(&["foo", "foo", "bar"])).dedup_chain();
+        assert_eq!(error.join_sources(": ").to_string(), "foo: bar");
+    }
+
+    #[test]
+    fn dedup_chain_keeps_distinct() {
+        let error = BoxedError::new(ChainError::new(&["foo", "bar", "foo"])).dedup_chain();
+        assert_eq!(error.join_sources(": ").to_string(), "foo: bar: foo");
+    }
+
+    #[test]
+    fn to_joined_string() {
+        let error = ChainError::new(&["foo", "bar"]);
+        assert_eq!(error.to_joined_string(": "), error.join_sources(": ").to_string());
+        let error: &dyn std::error::Error = &error;
+        assert_eq!(error.to_joined_string(" <- "), "foo <- bar");
+    }
+
+    #[test]
+    fn success() {
+        use std::process::{ExitCode, Termination};
+
+        fn run() -> crate::result::MultilineTerminator<super::Success<u32>> {
+            Ok(super::Success(42))
+        }
+
+        assert_eq!(run().report(), ExitCode::SUCCESS);
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn boxed_provide() {
+        use super::BoxedError;
+
+        #[derive(Debug)]
+        struct ProvidingError;
+
+        impl std::fmt::Display for ProvidingError {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("providing error")
+            }
+        }
+
+        impl std::error::Error for ProvidingError {
+            fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+                request.provide_value(42u32);
+            }
+        }
+
+        let error = BoxedError::new(ProvidingError);
+        assert_eq!(std::error::request_value::<u32>(&error), Some(42));
+    }
+
+    #[test]
+    fn smart_pointers() {
+        let error: Box<dyn std::error::Error> = Box::new(ChainError::new(&["foo", "bar"]));
+        assert_eq!(error.join_sources(": ").to_string(), "foo: bar");
+        assert_eq!(error.root_cause().to_string(), "bar");
+        let error: Box<dyn std::error::Error + Send + Sync> = Box::new(ChainError::new(&["foo", "bar"]));
+        assert_eq!(error.join_sources(": ").to_string(), "foo: bar");
+        let error: std::sync::Arc<dyn std::error::Error + Send + Sync> = std::sync::Arc::new(ChainError::new(&["foo", "bar"]));
+        assert_eq!(error.join_sources(": ").to_string(), "foo: bar");
+        assert_eq!(error.root_cause().to_string(), "bar");
+    }
+
+    #[test]
+    fn find_source_in_smart_pointers() {
+        use std::io;
+        use std::sync::Arc;
+
+        let error: Arc<dyn std::error::Error + Send + Sync> = Arc::new(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(error.find_source::<io::Error>().unwrap().kind(), io::ErrorKind::NotFound);
+        let error = Arc::new(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(error.find_source::<io::Error>().unwrap().kind(), io::ErrorKind::NotFound);
+        let error = Box::new(ChainError::new(&["foo", "bar"]));
+        assert_eq!(error.find_source::<ChainError>().unwrap().to_string(), "foo");
+        assert!(error.find_source::<io::Error>().is_none());
+
+        // pointers in the middle of the chain
+        #[derive(Debug, thiserror::Error)]
+        #[error("request failed")]
+        struct Wrapper(#[source] Arc<dyn std::error::Error + Send + Sync>);
+
+        let error = Wrapper(Arc::new(io::Error::from(io::ErrorKind::NotFound)));
+        assert_eq!(error.find_source::<io::Error>().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn cargo_style_terminator() {
+        use super::{CargoStyleTerminator, TerminationInfo};
+
+        let mut output = String::new();
+        CargoStyleTerminator::write_error(&mut output, &ChainError::new(&["foo", "bar"])).unwrap();
+        assert_eq!(output, "error: foo\n  note: caused by: bar");
+    }
+
+    #[test]
+    fn find_source() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("reading config failed")]
+        struct ConfigError(#[source] std::io::Error);
+
+        let error = ConfigError(std::io::ErrorKind::NotFound.into());
+        assert_eq!(error.find_source::<std::io::Error>().unwrap().kind(), std::io::ErrorKind::NotFound);
+        assert!(error.find_source::<ConfigError>().is_some());
+        let error: &dyn std::error::Error = &error;
+        assert!(error.find_source::<std::fmt::Error>().is_none());
+    }
+
+    #[test]
+    fn send_boxed_error() {
+        use super::{MultilineTerminator, SendBoxedError, TerminatingError};
+
+        let error = std::thread::spawn(|| TerminatingError::<MultilineTerminator, SendBoxedError>::from(ChainError::new(&["foo", "bar"])))
+            .join()
+            .unwrap();
+        assert!(format!("{:?}", error).contains("foo\n\tcaused by: bar"));
+        let error = BoxedError::from(SendBoxedError::from("oops"));
+        assert_eq!(error.to_string(), "oops");
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn display_chain_colored() {
+        use super::ColorChoice;
+
+        let error = ChainError::new(&["foo", "bar"]);
+        assert_eq!(error.display_chain_colored_with(ColorChoice::Never).to_string(), "foo\ncaused by: bar");
+        assert_eq!(error.display_chain_colored_with(ColorChoice::Always).to_string(), "\x1b[1m\x1b[31mfoo\x1b[0m\n\x1b[2mcaused by: bar\x1b[0m");
+    }
+
+    #[test]
+    fn write_chain() {
+        struct Wrapper(ChainError);
+
+        impl fmt::Display for Wrapper {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("wrapped(")?;
+                self.0.write_chain(&mut *f, " <- ")?;
+                f.write_str(")")
+            }
+        }
+
+        let error = ChainError::new(&["foo", "bar"]);
+        let mut output = String::new();
+        error.write_chain(&mut output, ": ").unwrap();
+        assert_eq!(output, error.join_sources(": ").to_string());
+        assert_eq!(Wrapper(error).to_string(), "wrapped(foo <- bar)");
+    }
+
+    #[test]
+    fn terminating_error_map() {
+        use super::{ContextError, MultilineTerminator, TerminatingError};
+
+        let error = TerminatingError::<MultilineTerminator, _>::new(ChainError::new(&["foo"]));
+        assert_eq!(error.inner().to_string(), "foo");
+        let error = error.map(|error| ContextError::new("bar", error));
+        assert_eq!(error.inner().join_sources(": ").to_string(), "bar: foo");
+        let error = error.into_inner();
+        assert_eq!(error.find_source::<ChainError>().unwrap().to_string(), "foo");
+    }
+
+    #[test]
+    fn named_terminator() {
+        use super::{AppName, NamedTerminator, TerminationInfo};
+
+        enum MyApp {}
+
+        impl AppName for MyApp {
+            const NAME: &'static str = "myapp";
+        }
+
+        let mut output = String::new();
+        NamedTerminator::<MyApp>::write_error(&mut output, &ChainError::new(&["foo", "bar"])).unwrap();
+        assert_eq!(output, "Application myapp failed: foo\n\tcaused by: bar");
+    }
 }
@@ -1,6 +1,18 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "nightly", feature(error_generic_member_access))]
+
+#[cfg(feature = "std")]
 pub mod result;
 pub mod ops;
+#[cfg(feature = "std")]
 pub mod error;
+#[cfg(feature = "std")]
+pub mod env;
+#[cfg(feature = "std")]
+pub mod collect;
+#[cfg(feature = "std")]
+pub mod parse;
+pub mod prelude;
 
 #[cfg(test)]
 mod tests {
@@ -8,4 +20,21 @@ mod tests {
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn prelude() {
+        use crate::prelude::*;
+
+        assert!(200u8.try_add(100).is_err());
+        assert_eq!(5u8.try_incr_mod(6).unwrap(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn prelude_std() {
+        use crate::prelude::*;
+
+        let error = 1u8.try_div(0).unwrap_err();
+        assert_eq!(error.join_sources(": ").to_string(), "attempted to divide 1 by zero");
+    }
 }
@@ -1,18 +1,146 @@
+use core::convert::TryInto;
+use core::num::{NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize};
+use core::time::Duration;
+
 /// Adds arithmetic operations similar to `checked_*` but returning Result with nice errors
-pub trait ArithmeticTryOps<RHS = Self> where Self: Sized + core::fmt::Display + core::fmt::Debug + TypeName, RHS: Sized + core::fmt::Display + core::fmt::Debug {
+///
+/// All methods take operands by value. Since the primitive types are `Copy`, the methods can be
+/// called on `&T` directly (the receiver gets copied by auto-deref). If the operand is a reference
+/// too, use `ArithmeticTryOpsRef`.
+pub trait ArithmeticTryOps<RHS = Self> where Self: Sized + core::fmt::Display + core::fmt::Debug + TypeName + BitWidth, RHS: Sized + core::fmt::Display + core::fmt::Debug {
+    /// Unsigned type of the same width (`Self` for unsigned types)
+    type Unsigned: ArithmeticTryOps;
+
     fn try_add(self, other: RHS) -> Result<Self, OverflowError<Self, RHS>>;
     fn try_sub(self, other: RHS) -> Result<Self, OverflowError<Self, RHS>>;
     fn try_mul(self, other: RHS) -> Result<Self, OverflowError<Self, RHS>>;
+    /// Computes `self * mul + add`
+    ///
+    /// The error says which of the two operations overflowed. Note that for signed types the
+    /// multiplication overflowing is reported even if adding a negative number would bring the
+    /// result back into range.
+    fn try_mul_add(self, mul: RHS, add: RHS) -> Result<Self, OverflowError<Self, RHS>> {
+        self.try_mul(mul)?.try_add(add)
+    }
+    /// Same as `try_add` but accepts any RHS losslessly convertible to `Self`
+    ///
+    /// E.g. `100u64.try_add_any(5u32)`. These are separate methods rather than more impls of the
+    /// trait because additional impls would break type inference of integer literals.
+    fn try_add_any<R: Into<Self>>(self, other: R) -> Result<Self, OverflowError<Self, Self>> where Self: ArithmeticTryOps {
+        <Self as ArithmeticTryOps>::try_add(self, other.into())
+    }
+    /// Same as `try_sub` but accepts any RHS losslessly convertible to `Self`
+    fn try_sub_any<R: Into<Self>>(self, other: R) -> Result<Self, OverflowError<Self, Self>> where Self: ArithmeticTryOps {
+        <Self as ArithmeticTryOps>::try_sub(self, other.into())
+    }
+    /// Same as `try_mul` but accepts any RHS losslessly convertible to `Self`
+    fn try_mul_any<R: Into<Self>>(self, other: R) -> Result<Self, OverflowError<Self, Self>> where Self: ArithmeticTryOps {
+        <Self as ArithmeticTryOps>::try_mul(self, other.into())
+    }
+    /// Same as `try_div` but accepts any RHS losslessly convertible to `Self`
+    fn try_div_any<R: Into<Self>>(self, other: R) -> Result<Self, DivisionByZeroError<Self>> where Self: ArithmeticTryOps {
+        <Self as ArithmeticTryOps>::try_div(self, other.into())
+    }
+    /// Same as `try_rem` but accepts any RHS losslessly convertible to `Self`
+    fn try_rem_any<R: Into<Self>>(self, other: R) -> Result<Self, DivisionByZeroError<Self>> where Self: ArithmeticTryOps {
+        <Self as ArithmeticTryOps>::try_rem(self, other.into())
+    }
     fn try_div(self, other: RHS) -> Result<Self, DivisionByZeroError<Self>>;
+    /// Returns quotient and remainder checking the divisor only once
+    ///
+    /// Apart from division by zero this fails for `MIN / -1` of signed types.
+    fn try_div_rem(self, other: RHS) -> Result<(Self, Self), DivisionError<Self>>;
+    /// Division rounding towards positive infinity
+    ///
+    /// Unlike `(a + b - 1) / b` this doesn't overflow for large `a`. Apart from division by zero
+    /// this fails for `MIN / -1` of signed types.
+    fn try_div_ceil(self, other: RHS) -> Result<Self, DivisionError<Self>>;
     fn try_div_euclid(self, other: RHS) -> Result<Self, DivisionByZeroError<Self>>;
+    /// Remainder with the sign of `self` (like `%` in C)
     fn try_rem(self, other: RHS) -> Result<Self, DivisionByZeroError<Self>>;
+    /// Remainder which is never negative, `0 <= result < |other|`
+    ///
+    /// This is the least non-negative residue used in modular arithmetic. For positive `other` it's
+    /// the same as the common `((a % m) + m) % m` idiom except the intermediate addition can't
+    /// overflow. For negative `other` the idiom returns non-positive values while this doesn't.
     fn try_rem_euclid(self, other: RHS) -> Result<Self, DivisionByZeroError<Self>>;
-    fn try_pow(self, other: u32) -> Result<Self, OverflowError<Self, u32>>;
-    //fn try_next_power_of_two(self) -> Result<Self, NextPowerOfTwoError<Self>;
+    /// Mathematical modulo, never negative
+    ///
+    /// This is the same as `try_rem_euclid`, the alias is provided for people coming from languages
+    /// where `mod` is the name of this operation.
+    fn try_mod(self, other: RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        self.try_rem_euclid(other)
+    }
+    /// Least non-negative residue, replacement for the `((a % m) + m) % m` idiom
+    ///
+    /// This is the same as `try_rem_euclid` (and `try_mod`), it's provided under the name people
+    /// look for when implementing ring arithmetic. The result is in `0..|modulus|` even for negative
+    /// `modulus`, so `(-7).wrapping_rem_nonneg(-3)` is `2` while the idiom gives `-1`.
+    fn wrapping_rem_nonneg(self, modulus: RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        self.try_rem_euclid(modulus)
+    }
+    /// Exponentiation, the error message contains the type of `self`
+    ///
+    /// Powers overflow much sooner than other operations, so knowing the type helps when reading
+    /// logs.
+    fn try_pow(self, other: u32) -> Result<Self, TypedOverflowError<Self, u32>>;
+    /// Same as `try_pow` but accepts any exponent type convertible to `u32`
+    ///
+    /// Fails if the exponent doesn't fit into `u32` (including negative exponents) or if the
+    /// result overflows.
+    fn try_pow_any<E: TryInto<u32>>(self, exp: E) -> Result<Self, PowError<Self>> {
+        match exp.try_into() {
+            Ok(exp) => Ok(self.try_pow(exp)?),
+            Err(_) => Err(PowError::ExponentOutOfRange { base: self }),
+        }
+    }
     fn try_shl(self, other: u32) -> Result<Self, BigShiftError<Self>>;
     fn try_shr(self, other: u32) -> Result<Self, BigShiftError<Self>>;
+    /// Same as `try_shl` but accepts any type of shift amount
+    ///
+    /// Amounts that don't fit into `u32` (including negative) are reported as `BigShiftError` too.
+    fn try_shl_any<S>(self, amount: S) -> Result<Self, BigShiftError<Self, S>> where S: TryInto<u32> + Copy + core::fmt::Display + core::fmt::Debug {
+        match amount.try_into() {
+            Ok(converted) => self.try_shl(converted).map_err(|error| BigShiftError::new(error.left, "<<", amount)),
+            Err(_) => Err(BigShiftError::new(self, "<<", amount)),
+        }
+    }
+    /// Same as `try_shr` but accepts any type of shift amount
+    ///
+    /// Amounts that don't fit into `u32` (including negative) are reported as `BigShiftError` too.
+    fn try_shr_any<S>(self, amount: S) -> Result<Self, BigShiftError<Self, S>> where S: TryInto<u32> + Copy + core::fmt::Display + core::fmt::Debug {
+        match amount.try_into() {
+            Ok(converted) => self.try_shr(converted).map_err(|error| BigShiftError::new(error.left, ">>", amount)),
+            Err(_) => Err(BigShiftError::new(self, ">>", amount)),
+        }
+    }
+    /// Floor of square root, fails for negative values
+    fn try_isqrt(self) -> Result<Self, DomainError<Self>>;
+    /// Restricts the value to `min..=max`, fails if `min > max`
+    ///
+    /// Unlike `Ord::clamp` this doesn't panic on inverted bounds which is useful when they come
+    /// from user input.
+    fn try_clamp(self, min: Self, max: Self) -> Result<Self, InvalidBoundsError<Self>> where Self: Ord {
+        if min > max {
+            Err(InvalidBoundsError { min, max })
+        } else {
+            Ok(self.clamp(min, max))
+        }
+    }
+    /// Absolute difference, never overflows
+    ///
+    /// The result is unsigned because the difference of signed values may not fit into the
+    /// signed type (e.g. `i8::MAX - i8::MIN`).
+    fn abs_diff(self, other: RHS) -> Self::Unsigned;
+    /// Average of `self` and `other`, never overflows
+    ///
+    /// Same as `midpoint` of std integers: unsigned results are rounded down, signed results are
+    /// rounded towards zero. It's named `try_*` like the rest of the trait even though it can't
+    /// fail, so that it isn't shadowed by the inherent `midpoint` of integers.
+    fn try_midpoint(self, other: RHS) -> Self;
 }
 
+/// Returned when the result doesn't fit into the type of LHS
 #[derive(Debug, thiserror::Error)]
 #[error("operation {left} {op} {right} overflowed")]
 pub struct OverflowError<L: core::fmt::Display + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> {
@@ -21,23 +149,458 @@ pub struct OverflowError<L: core::fmt::Display + core::fmt::Debug, R: core::fmt:
     right: R,
 }
 
+impl<L: core::fmt::Display + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> OverflowError<L, R> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    pub fn new(left: L, op: &'static str, right: R) -> Self {
+        OverflowError { left, op, right }
+    }
+}
+
+/// Same as `OverflowError` but the message also contains the type of LHS
+///
+/// A separate type, so that the message of `OverflowError` stays the same. Returned from
+/// `try_pow`.
+#[derive(Debug, thiserror::Error)]
+#[error("operation {left} {op} {right} overflowed (the type of LHS is {})", L::type_name())]
+pub struct TypedOverflowError<L: core::fmt::Display + TypeName + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> {
+    left: L,
+    op: &'static str,
+    right: R,
+}
+
+impl<L: core::fmt::Display + TypeName + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> TypedOverflowError<L, R> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    pub fn new(left: L, op: &'static str, right: R) -> Self {
+        TypedOverflowError { left, op, right }
+    }
+}
+
+/// Checked operations taking operands by reference
+///
+/// Helps in generic code holding `&T`, so it doesn't have to dereference the operands. The values
+/// are copied before performing the operation, so this is implemented for all `Copy` types
+/// implementing `ArithmeticTryOps`.
+pub trait ArithmeticTryOpsRef<RHS = Self>: ArithmeticTryOps<RHS> + Copy where RHS: Copy + core::fmt::Display + core::fmt::Debug {
+    fn try_add_ref(&self, other: &RHS) -> Result<Self, OverflowError<Self, RHS>> {
+        (*self).try_add(*other)
+    }
+
+    fn try_sub_ref(&self, other: &RHS) -> Result<Self, OverflowError<Self, RHS>> {
+        (*self).try_sub(*other)
+    }
+
+    fn try_mul_ref(&self, other: &RHS) -> Result<Self, OverflowError<Self, RHS>> {
+        (*self).try_mul(*other)
+    }
+
+    fn try_div_ref(&self, other: &RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        (*self).try_div(*other)
+    }
+
+    fn try_div_rem_ref(&self, other: &RHS) -> Result<(Self, Self), DivisionError<Self>> {
+        (*self).try_div_rem(*other)
+    }
+
+    fn try_div_euclid_ref(&self, other: &RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        (*self).try_div_euclid(*other)
+    }
+
+    fn try_rem_ref(&self, other: &RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        (*self).try_rem(*other)
+    }
+
+    fn try_rem_euclid_ref(&self, other: &RHS) -> Result<Self, DivisionByZeroError<Self>> {
+        (*self).try_rem_euclid(*other)
+    }
+}
+
+impl<T, RHS> ArithmeticTryOpsRef<RHS> for T where T: ArithmeticTryOps<RHS> + Copy, RHS: Copy + core::fmt::Display + core::fmt::Debug {}
+
+/// In-place checked operations
+///
+/// On success `self` is updated, on failure it's left unchanged and the error carries the
+/// original operands.
+pub trait ArithmeticTryAssign<RHS = Self>: ArithmeticTryOps<RHS> + Copy where RHS: core::fmt::Display + core::fmt::Debug {
+    fn try_add_assign(&mut self, other: RHS) -> Result<(), OverflowError<Self, RHS>> {
+        *self = self.try_add(other)?;
+        Ok(())
+    }
+
+    fn try_sub_assign(&mut self, other: RHS) -> Result<(), OverflowError<Self, RHS>> {
+        *self = self.try_sub(other)?;
+        Ok(())
+    }
+
+    fn try_mul_assign(&mut self, other: RHS) -> Result<(), OverflowError<Self, RHS>> {
+        *self = self.try_mul(other)?;
+        Ok(())
+    }
+
+    fn try_div_assign(&mut self, other: RHS) -> Result<(), DivisionByZeroError<Self>> {
+        *self = self.try_div(other)?;
+        Ok(())
+    }
+
+    fn try_rem_assign(&mut self, other: RHS) -> Result<(), DivisionByZeroError<Self>> {
+        *self = self.try_rem(other)?;
+        Ok(())
+    }
+
+    fn try_shl_assign(&mut self, other: u32) -> Result<(), BigShiftError<Self>> {
+        *self = self.try_shl(other)?;
+        Ok(())
+    }
+
+    fn try_shr_assign(&mut self, other: u32) -> Result<(), BigShiftError<Self>> {
+        *self = self.try_shr(other)?;
+        Ok(())
+    }
+}
+
+impl<T, RHS> ArithmeticTryAssign<RHS> for T where T: ArithmeticTryOps<RHS> + Copy, RHS: core::fmt::Display + core::fmt::Debug {}
+
+/// Checked sum and product of iterator items
+///
+/// Both short-circuit on the first overflow. The error carries the partial accumulator and the
+/// offending operand.
+pub trait TryArithIterExt: Iterator + Sized {
+    fn try_sum<S>(mut self) -> Result<S, OverflowError<S, S>> where Self: Iterator<Item = S>, S: ArithmeticTryOps + Default {
+        self.try_fold(S::default(), ArithmeticTryOps::try_add)
+    }
+
+    fn try_product<S>(mut self) -> Result<S, OverflowError<S, S>> where Self: Iterator<Item = S>, S: ArithmeticTryOps + core::iter::Product {
+        // empty product is the easiest way to get one
+        self.try_fold(core::iter::empty::<S>().product(), ArithmeticTryOps::try_mul)
+    }
+
+    /// Sums the items in a (usually wider) accumulator type
+    ///
+    /// E.g. summing `u8`s into `u32` overflows much later than summing them as `u8`.
+    fn try_sum_into<Acc>(mut self) -> Result<Acc, OverflowError<Acc, Acc>> where Self::Item: Into<Acc>, Acc: ArithmeticTryOps + Default {
+        self.try_fold(Acc::default(), |acc, item| acc.try_add(item.into()))
+    }
+}
+
+impl<I: Iterator> TryArithIterExt for I {}
+
+/// Evaluates polynomial at `x` using Horner's method with overflow checking
+///
+/// The coefficients are ordered from the highest degree, so `[a, b, c]` evaluates `a*x*x + b*x + c`.
+/// Empty slice evaluates to zero (`Default`). The error describes the operation that overflowed.
+pub fn try_horner<T: ArithmeticTryOps + Copy + Default>(coeffs: &[T], x: T) -> Result<T, OverflowError<T, T>> {
+    coeffs.iter().try_fold(T::default(), |acc, coeff| acc.try_mul(x)?.try_add(*coeff))
+}
+
+/// Additional operations that only make sense for unsigned types
+pub trait UnsignedTryOps: ArithmeticTryOps {
+    /// Computes `(self + 1) % modulus` without overflowing
+    ///
+    /// Handy for sequence counters wrapping at a known modulus.
+    fn try_incr_mod(self, modulus: Self) -> Result<Self, DivisionByZeroError<Self>>;
+
+    /// Signed type of the same width
+    type Signed: ArithmeticTryOps;
+
+    /// Same as `try_sub` but the error contains the amount by which `other` exceeds `self`
+    ///
+    /// Useful for budget and quota code that wants to report "over by N".
+    fn try_sub_or_deficit(self, other: Self) -> Result<Self, DeficitError<Self>>;
+
+    /// Rounds up to the nearest multiple of `rhs`
+    ///
+    /// Useful for alignment. Fails if `rhs` is zero or if the result doesn't fit.
+    fn try_next_multiple_of(self, rhs: Self) -> Result<Self, NextMultipleError<Self>>;
+
+    /// Returns the smallest power of two greater than or equal to `self`
+    ///
+    /// Fails if the power doesn't fit into the type.
+    fn try_next_power_of_two(self) -> Result<Self, NextPowerOfTwoError<Self>>;
+
+    /// Number of bytes needed to encode the value as LEB128 (base-128 groups, at least 1)
+    fn leb128_len(self) -> u32;
+
+    /// Converts to the signed type of the same width, fails if the value is too big
+    ///
+    /// Unlike `as` this never wraps.
+    fn try_to_signed(self) -> Result<Self::Signed, SignedOverflowError<Self>>;
+
+    /// Computes `self - other` as the signed type of the same width
+    ///
+    /// Fails only if the difference doesn't fit into the signed type. Avoids `as` casts which
+    /// produce garbage for large values.
+    fn signed_diff(self, other: Self) -> Result<Self::Signed, SignedDiffError<Self>>;
+}
+
+/// Additional operations that only make sense for signed types
+pub trait SignedTryOps: ArithmeticTryOps {
+    /// Converts to the unsigned type of the same width, fails if the value is negative
+    ///
+    /// Unlike `as` this never wraps.
+    fn try_to_unsigned(self) -> Result<Self::Unsigned, NegativeError<Self>>;
+}
+
+/// Checked operations that also provide the wrapped result on overflow
+///
+/// Same as `overflowing_*` methods of integers but the error describes the operation.
+pub trait OverflowingTryOps: ArithmeticTryOps {
+    fn try_add_overflowing(self, other: Self) -> Result<Self, WrappedOverflowError<Self>>;
+    fn try_sub_overflowing(self, other: Self) -> Result<Self, WrappedOverflowError<Self>>;
+    fn try_mul_overflowing(self, other: Self) -> Result<Self, WrappedOverflowError<Self>>;
+}
+
+/// `OverflowError` carrying the wrapped result of the operation
+#[derive(Debug, thiserror::Error)]
+#[error("{error}")]
+pub struct WrappedOverflowError<T: core::fmt::Display + TypeName + core::fmt::Debug> {
+    wrapped: T,
+    error: OverflowError<T, T>,
+}
+
+impl<T: core::fmt::Display + TypeName + core::fmt::Debug + Copy> WrappedOverflowError<T> {
+    /// Used when implementing `OverflowingTryOps` for custom types
+    pub fn new(wrapped: T, error: OverflowError<T, T>) -> Self {
+        WrappedOverflowError { wrapped, error }
+    }
+
+    /// Result of the operation wrapped around at the bounds of the type
+    pub fn wrapped(&self) -> T {
+        self.wrapped
+    }
+
+    /// Returns the wrapped result and the error without it
+    pub fn into_parts(self) -> (T, OverflowError<T, T>) {
+        (self.wrapped, self.error)
+    }
+}
+
+/// Bit manipulation helpers
+///
+/// The counting methods are the same as the inherent methods of integers, the trait makes them
+/// available in generic code.
+pub trait BitOps: Sized + core::fmt::Display + core::fmt::Debug + TypeName + BitWidth {
+    fn leading_zeros(self) -> u32;
+    fn trailing_zeros(self) -> u32;
+    fn count_ones(self) -> u32;
+
+    /// Returns the bit at `index` (0 is the least significant bit)
+    ///
+    /// Fails if `index` is not less than the width of the type. The error describes the failure as
+    /// the equivalent shift (`self >> index`).
+    fn checked_bit(self, index: u32) -> Result<bool, BigShiftError<Self>>;
+}
+
+/// Returned from `UnsignedTryOps::try_sub_or_deficit` when `other > self`
+#[derive(Debug, thiserror::Error)]
+#[error("operation {left} - {right} underflowed by {deficit} (the type of LHS is {})", T::type_name())]
+pub struct DeficitError<T: UnsignedTryOps> {
+    left: T,
+    right: T,
+    deficit: T,
+}
+
+impl<T: UnsignedTryOps + Copy> DeficitError<T> {
+    /// Used when implementing `UnsignedTryOps` for custom types
+    pub fn new(left: T, right: T, deficit: T) -> Self {
+        DeficitError { left, right, deficit }
+    }
+
+    /// By how much `right` exceeds `left` (`right - left`)
+    pub fn deficit(&self) -> T {
+        self.deficit
+    }
+}
+
+/// Returned from `UnsignedTryOps::try_next_multiple_of`
+#[derive(Debug, thiserror::Error)]
+pub enum NextMultipleError<T: UnsignedTryOps> {
+    #[error("cannot round {value} up to a multiple of zero")]
+    ZeroMultiple { value: T },
+    #[error("rounding {value} up to a multiple of {rhs} overflowed (the type is {})", T::type_name())]
+    Overflow { value: T, rhs: T },
+}
+
+/// Returned from `UnsignedTryOps::try_next_power_of_two`
+#[derive(Debug, thiserror::Error)]
+#[error("the smallest power of two greater than or equal to {0} overflows {ty}", ty = T::type_name())]
+pub struct NextPowerOfTwoError<T: UnsignedTryOps>(T);
+
+impl<T: UnsignedTryOps> NextPowerOfTwoError<T> {
+    /// Used when implementing `UnsignedTryOps` for custom types
+    pub fn new(value: T) -> Self {
+        NextPowerOfTwoError(value)
+    }
+}
+
+/// Returned when converting a negative value to an unsigned type
+#[derive(Debug, thiserror::Error)]
+#[error("cannot convert negative value {0} of type {ty} to {target}", ty = T::type_name(), target = <T::Unsigned as TypeName>::type_name())]
+pub struct NegativeError<T: SignedTryOps>(T);
+
+impl<T: SignedTryOps> NegativeError<T> {
+    /// Used when implementing `SignedTryOps` for custom types
+    pub fn new(value: T) -> Self {
+        NegativeError(value)
+    }
+}
+
+/// Returned when an unsigned value doesn't fit into the signed type of the same width
+#[derive(Debug, thiserror::Error)]
+#[error("value {0} of type {ty} doesn't fit into {target}", ty = T::type_name(), target = <T::Signed as TypeName>::type_name())]
+pub struct SignedOverflowError<T: UnsignedTryOps>(T);
+
+impl<T: UnsignedTryOps> SignedOverflowError<T> {
+    /// Used when implementing `UnsignedTryOps` for custom types
+    pub fn new(value: T) -> Self {
+        SignedOverflowError(value)
+    }
+}
+
+/// Returned from `UnsignedTryOps::signed_diff` when the difference doesn't fit into the signed type
+#[derive(Debug, thiserror::Error)]
+#[error("difference {left} - {right} doesn't fit into {target}", target = <T::Signed as TypeName>::type_name())]
+pub struct SignedDiffError<T: UnsignedTryOps> {
+    left: T,
+    right: T,
+}
+
+impl<T: UnsignedTryOps> SignedDiffError<T> {
+    /// Used when implementing `UnsignedTryOps` for custom types
+    pub fn new(left: T, right: T) -> Self {
+        SignedDiffError { left, right }
+    }
+}
+
+/// Number-theoretic functions for unsigned types
+pub trait NumTheory: UnsignedTryOps {
+    /// Greatest common divisor, never overflows
+    ///
+    /// `gcd(0, 0)` is `0`.
+    fn gcd(self, other: Self) -> Self;
+
+    /// Least common multiple
+    ///
+    /// Computed as `a / gcd(a, b) * b`, so it only fails if the result doesn't fit. If either
+    /// operand is `0` the result is `0`.
+    fn try_lcm(self, other: Self) -> Result<Self, OverflowError<Self, Self>>;
+
+    /// Computes `self.pow(exp) % modulus` without intermediate overflow
+    ///
+    /// Uses exponentiation by squaring, fails only if `modulus` is zero.
+    fn try_pow_mod(self, exp: u32, modulus: Self) -> Result<Self, DivisionByZeroError<Self>>;
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("attempted to divide {0} by zero")]
 pub struct DivisionByZeroError<T: core::fmt::Display + core::fmt::Debug>(T);
 
+impl<T: core::fmt::Display + core::fmt::Debug> DivisionByZeroError<T> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    pub fn new(dividend: T) -> Self {
+        DivisionByZeroError(dividend)
+    }
+}
+
+/// Returned from division that can overflow
+///
+/// Integer division only overflows for `MIN / -1` of signed types, this tells it apart from
+/// division by zero.
+#[derive(Debug, thiserror::Error)]
+pub enum DivisionError<T: core::fmt::Display + core::fmt::Debug> {
+    #[error(transparent)]
+    DivisionByZero(#[from] DivisionByZeroError<T>),
+    #[error(transparent)]
+    Overflow(#[from] OverflowError<T, T>),
+}
+
+impl<T: core::fmt::Display + core::fmt::Debug> DivisionError<T> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    ///
+    /// Returns `DivisionByZero` if `divisor_is_zero` is true, `Overflow` otherwise.
+    pub fn new(dividend: T, op: &'static str, divisor: T, divisor_is_zero: bool) -> Self {
+        if divisor_is_zero {
+            DivisionError::DivisionByZero(DivisionByZeroError(dividend))
+        } else {
+            DivisionError::Overflow(OverflowError { left: dividend, op, right: divisor })
+        }
+    }
+}
+
+/// Returned when attempting to compute square root of a negative value
+#[derive(Debug, thiserror::Error)]
+#[error("cannot take sqrt of negative value {0}")]
+pub struct DomainError<T: core::fmt::Display + core::fmt::Debug>(T);
+
+impl<T: core::fmt::Display + core::fmt::Debug> DomainError<T> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    pub fn new(value: T) -> Self {
+        DomainError(value)
+    }
+}
+
 /// Retrurned from << and >> when RHS is too much
+///
+/// `R` is the type of the shift amount, it's only different from `u32` for `try_sh*_any`.
 #[derive(Debug, thiserror::Error)]
-#[error("operation {left} {op} {right} attempted to shift too much (the type of LHS is {})", L::type_name())]
-pub struct BigShiftError<L: core::fmt::Display + TypeName + core::fmt::Debug> {
+#[error("operation {left} {op} {right} failed: shift amount must be less than {} (the width of {})", L::BIT_WIDTH, L::type_name())]
+pub struct BigShiftError<L: core::fmt::Display + TypeName + BitWidth + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug = u32> {
     left: L,
     op: &'static str,
-    right: u32,
+    right: R,
+}
+
+impl<L: core::fmt::Display + TypeName + BitWidth + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> BigShiftError<L, R> {
+    /// Used when implementing `ArithmeticTryOps` for custom types
+    pub fn new(left: L, op: &'static str, right: R) -> Self {
+        BigShiftError { left, op, right }
+    }
+}
+
+/// Returned from `ArithmeticTryOps::try_clamp` if `min > max`
+#[derive(Debug, thiserror::Error)]
+#[error("invalid bounds: minimum {min} is greater than maximum {max}")]
+pub struct InvalidBoundsError<T: core::fmt::Display + core::fmt::Debug> {
+    min: T,
+    max: T,
+}
+
+/// Returned from `ArithmeticTryOps::try_pow_any`
+#[derive(Debug, thiserror::Error)]
+pub enum PowError<T: TypeName + core::fmt::Display + core::fmt::Debug> {
+    #[error("the exponent for base {base} is out of range of u32")]
+    ExponentOutOfRange { base: T },
+    #[error(transparent)]
+    Overflow(#[from] TypedOverflowError<T, u32>),
+}
+
+/// Any error returned from `ArithmeticTryOps`
+///
+/// Allows functions performing several different checked operations to return a single error
+/// type. All the individual errors convert into it, so `?` just works.
+#[derive(Debug, thiserror::Error)]
+pub enum ArithmeticError<L: core::fmt::Display + TypeName + BitWidth + core::fmt::Debug, R: core::fmt::Display + core::fmt::Debug> {
+    #[error(transparent)]
+    Overflow(#[from] OverflowError<L, R>),
+    #[error(transparent)]
+    TypedOverflow(#[from] TypedOverflowError<L, u32>),
+    #[error(transparent)]
+    DivisionByZero(#[from] DivisionByZeroError<L>),
+    #[error(transparent)]
+    Division(#[from] DivisionError<L>),
+    #[error(transparent)]
+    BigShift(#[from] BigShiftError<L>),
+    #[error(transparent)]
+    Domain(#[from] DomainError<L>),
 }
 
 pub trait TypeName {
     fn type_name() -> &'static str;
 }
 
+/// Number of bits of the type
+pub trait BitWidth {
+    const BIT_WIDTH: u32;
+}
+
 macro_rules! impl_type_names {
     ($($type:ty),*) => {
         $(
@@ -62,22 +625,110 @@ macro_rules! impl_overflowing_op {
     }
 }
 
+macro_rules! impl_isqrt {
+    (unsigned, $self:expr) => {
+        Ok($self.isqrt())
+    };
+    (signed, $self:expr) => {
+        $self.checked_isqrt().ok_or(DomainError($self))
+    };
+}
+
+macro_rules! impl_div_ceil {
+    (unsigned, $self:expr, $other:expr) => {
+        if $other == 0 {
+            Err(DivisionError::DivisionByZero(DivisionByZeroError($self)))
+        } else {
+            Ok($self.div_ceil($other))
+        }
+    };
+    (signed, $self:expr, $other:expr) => {
+        {
+            if $other == 0 {
+                return Err(DivisionError::DivisionByZero(DivisionByZeroError($self)));
+            }
+            $self.checked_div($other).map(|quotient| {
+                // if the division succeeded the remainder can't fail
+                let remainder = $self % $other;
+                // the quotient is truncated so it has to be incremented if the exact result is positive
+                // |other| > 1 in such case, so this can't overflow
+                if remainder != 0 && (remainder > 0) == ($other > 0) {
+                    quotient + 1
+                } else {
+                    quotient
+                }
+            }).ok_or(DivisionError::Overflow(OverflowError {
+                left: $self,
+                op: "div_ceil",
+                right: $other,
+            }))
+        }
+    };
+}
+
+macro_rules! impl_midpoint {
+    (unsigned, $self:expr, $other:expr) => {
+        $self.midpoint($other)
+    };
+    (signed, $self:expr, $other:expr) => {
+        {
+            // floor of the average, the shifts are arithmetic
+            let floor = ($self >> 1) + ($other >> 1) + ($self & $other & 1);
+            // round towards zero if the average is negative and not exact
+            if floor < 0 && ($self ^ $other) & 1 == 1 {
+                floor + 1
+            } else {
+                floor
+            }
+        }
+    };
+}
+
 macro_rules! impl_arith_op {
-    ($($type:ty),*) => {
+    ($kind:ident: $($type:ty => $unsigned:ty),*) => {
         $(
             impl_type_names!($type);
 
+            impl BitWidth for $type {
+                const BIT_WIDTH: u32 = <$type>::BITS;
+            }
+
             impl ArithmeticTryOps for $type {
+                type Unsigned = $unsigned;
+
                 impl_overflowing_op!(try_add, checked_add, $type, "+");
                 impl_overflowing_op!(try_sub, checked_sub, $type, "-");
                 impl_overflowing_op!(try_mul, checked_mul, $type, "*");
-                // we don't use ^ to avoid mistaking it for bit xor
-                impl_overflowing_op!(try_pow, checked_pow, u32, "**");
+
+                fn try_pow(self, other: u32) -> Result<Self, TypedOverflowError<Self, u32>> {
+                    // we don't use ^ to avoid mistaking it for bit xor
+                    self.checked_pow(other).ok_or(TypedOverflowError {
+                        left: self,
+                        op: "**",
+                        right: other,
+                    })
+                }
 
                 fn try_div(self, other: Self) -> Result<Self, DivisionByZeroError<Self>> {
                     self.checked_div(other).ok_or(DivisionByZeroError(self))
                 }
 
+                fn try_div_rem(self, other: Self) -> Result<(Self, Self), DivisionError<Self>> {
+                    if other == 0 {
+                        return Err(DivisionError::DivisionByZero(DivisionByZeroError(self)));
+                    }
+                    // if the division succeeded the remainder can't fail
+                    self.checked_div(other).map(|quotient| (quotient, self % other)).ok_or(DivisionError::Overflow(OverflowError {
+                        left: self,
+                        op: "/",
+                        right: other,
+                    }))
+                }
+
+                fn try_div_ceil(self, other: Self) -> Result<Self, DivisionError<Self>> {
+                    impl_div_ceil!($kind, self, other)
+                }
+
                 fn try_div_euclid(self, other: Self) -> Result<Self, DivisionByZeroError<Self>> {
                     self.checked_div_euclid(other).ok_or(DivisionByZeroError(self))
                 }
@@ -105,19 +756,965 @@ macro_rules! impl_arith_op {
                         right: other,
                     })
                 }
+
+                fn try_isqrt(self) -> Result<Self, DomainError<Self>> {
+                    impl_isqrt!($kind, self)
+                }
+
+                fn abs_diff(self, other: Self) -> Self::Unsigned {
+                    <$type>::abs_diff(self, other)
+                }
+
+                fn try_midpoint(self, other: Self) -> Self {
+                    impl_midpoint!($kind, self, other)
+                }
             }
         )*
     }
 }
 
-impl_arith_op!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+/// Implements `ArithmeticTryOps` for a custom type that has `checked_*` methods
+///
+/// Usage: `impl_try_ops_from_checked!(MyType)` or `impl_try_ops_from_checked!(MyType => MyUnsigned)`
+/// to set `ArithmeticTryOps::Unsigned` (defaults to `MyType`).
+///
+/// The type must be `Copy + Display + Debug + TypeName + BitWidth` and have these methods (same
+/// signatures as the methods of primitive integers):
+///
+/// * `checked_add`, `checked_sub`, `checked_mul`, `checked_div`, `checked_rem`,
+///   `checked_div_euclid`, `checked_rem_euclid`, `checked_div_ceil` - `fn(self, Self) -> Option<Self>`
+/// * `checked_pow`, `checked_shl`, `checked_shr` - `fn(self, u32) -> Option<Self>`
+/// * `checked_isqrt` - `fn(self) -> Option<Self>`
+/// * `abs_diff` - `fn(self, Self) -> Unsigned`
+/// * `midpoint` - `fn(self, Self) -> Self`
+///
+/// All of them must be inherent methods. A missing `abs_diff` is a compile error (ambiguous method
+/// call) rather than a call of `ArithmeticTryOps::abs_diff` recursing forever.
+#[macro_export]
+macro_rules! impl_try_ops_from_checked {
+    ($type:ty) => {
+        $crate::impl_try_ops_from_checked!($type => $type);
+    };
+    ($type:ty => $unsigned:ty) => {
+        impl $crate::ops::ArithmeticTryOps for $type {
+            type Unsigned = $unsigned;
 
-#[cfg(test)]
-mod tests {
-    use super::ArithmeticTryOps;
+            fn try_add(self, other: Self) -> ::core::result::Result<Self, $crate::ops::OverflowError<Self, Self>> {
+                self.checked_add(other).ok_or_else(|| $crate::ops::OverflowError::new(self, "+", other))
+            }
 
-    #[test]
-    fn add() {
-        assert!(255u8.try_add(1).is_err());
+            fn try_sub(self, other: Self) -> ::core::result::Result<Self, $crate::ops::OverflowError<Self, Self>> {
+                self.checked_sub(other).ok_or_else(|| $crate::ops::OverflowError::new(self, "-", other))
+            }
+
+            fn try_mul(self, other: Self) -> ::core::result::Result<Self, $crate::ops::OverflowError<Self, Self>> {
+                self.checked_mul(other).ok_or_else(|| $crate::ops::OverflowError::new(self, "*", other))
+            }
+
+            fn try_pow(self, other: u32) -> ::core::result::Result<Self, $crate::ops::TypedOverflowError<Self, u32>> {
+                self.checked_pow(other).ok_or_else(|| $crate::ops::TypedOverflowError::new(self, "**", other))
+            }
+
+            fn try_div(self, other: Self) -> ::core::result::Result<Self, $crate::ops::DivisionByZeroError<Self>> {
+                self.checked_div(other).ok_or_else(|| $crate::ops::DivisionByZeroError::new(self))
+            }
+
+            // x / x only fails if x is zero, so it tells division by zero apart from MIN / -1
+            fn try_div_rem(self, other: Self) -> ::core::result::Result<(Self, Self), $crate::ops::DivisionError<Self>> {
+                self.checked_div(other)
+                    .and_then(|quotient| self.checked_rem(other).map(|remainder| (quotient, remainder)))
+                    .ok_or_else(|| $crate::ops::DivisionError::new(self, "/", other, other.checked_div(other).is_none()))
+            }
+
+            fn try_div_ceil(self, other: Self) -> ::core::result::Result<Self, $crate::ops::DivisionError<Self>> {
+                self.checked_div_ceil(other)
+                    .ok_or_else(|| $crate::ops::DivisionError::new(self, "div_ceil", other, other.checked_div(other).is_none()))
+            }
+
+            fn try_div_euclid(self, other: Self) -> ::core::result::Result<Self, $crate::ops::DivisionByZeroError<Self>> {
+                self.checked_div_euclid(other).ok_or_else(|| $crate::ops::DivisionByZeroError::new(self))
+            }
+
+            fn try_rem(self, other: Self) -> ::core::result::Result<Self, $crate::ops::DivisionByZeroError<Self>> {
+                self.checked_rem(other).ok_or_else(|| $crate::ops::DivisionByZeroError::new(self))
+            }
+
+            fn try_rem_euclid(self, other: Self) -> ::core::result::Result<Self, $crate::ops::DivisionByZeroError<Self>> {
+                self.checked_rem_euclid(other).ok_or_else(|| $crate::ops::DivisionByZeroError::new(self))
+            }
+
+            fn try_shl(self, other: u32) -> ::core::result::Result<Self, $crate::ops::BigShiftError<Self>> {
+                self.checked_shl(other).ok_or_else(|| $crate::ops::BigShiftError::new(self, "<<", other))
+            }
+
+            fn try_shr(self, other: u32) -> ::core::result::Result<Self, $crate::ops::BigShiftError<Self>> {
+                self.checked_shr(other).ok_or_else(|| $crate::ops::BigShiftError::new(self, ">>", other))
+            }
+
+            fn try_isqrt(self) -> ::core::result::Result<Self, $crate::ops::DomainError<Self>> {
+                self.checked_isqrt().ok_or_else(|| $crate::ops::DomainError::new(self))
+            }
+
+            fn abs_diff(self, other: Self) -> Self::Unsigned {
+                #[allow(unused_imports)]
+                use $crate::ops::__private::RequireInherentAbsDiff as _;
+                self.abs_diff(other)
+            }
+
+            fn try_midpoint(self, other: Self) -> Self {
+                self.midpoint(other)
+            }
+        }
+    };
+}
+
+/// Implementation details of exported macros
+#[doc(hidden)]
+pub mod __private {
+    /// Used by `impl_try_ops_from_checked!` to reject types without inherent `abs_diff`
+    ///
+    /// Without an inherent method `self.abs_diff(other)` inside the generated
+    /// `ArithmeticTryOps::abs_diff` would call itself. With this trait in scope the call is
+    /// ambiguous instead, so it fails to compile. It lives in a separate module so that glob
+    /// imports of `ops` don't make generic `abs_diff` calls ambiguous.
+    pub trait RequireInherentAbsDiff: Sized {
+        fn abs_diff(self, other: Self) -> MissingInherentAbsDiff;
+    }
+
+    pub enum MissingInherentAbsDiff {}
+
+    impl<T> RequireInherentAbsDiff for T {
+        fn abs_diff(self, _other: Self) -> MissingInherentAbsDiff {
+            unreachable!("the call is always ambiguous")
+        }
+    }
+}
+
+macro_rules! impl_unsigned_op {
+    ($($type:ty => $signed:ty),*) => {
+        $(
+            impl UnsignedTryOps for $type {
+                type Signed = $signed;
+
+                fn try_incr_mod(self, modulus: Self) -> Result<Self, DivisionByZeroError<Self>> {
+                    // self % modulus + 1 <= modulus, so this can't overflow
+                    self.checked_rem(modulus)
+                        .map(|rem| (rem + 1) % modulus)
+                        .ok_or(DivisionByZeroError(self))
+                }
+
+                fn leb128_len(self) -> u32 {
+                    let significant_bits = (<$type>::BITS - self.leading_zeros()).max(1);
+                    significant_bits.div_ceil(7)
+                }
+
+                fn try_sub_or_deficit(self, other: Self) -> Result<Self, DeficitError<Self>> {
+                    self.checked_sub(other).ok_or_else(|| DeficitError {
+                        left: self,
+                        right: other,
+                        // checked_sub failed so other > self
+                        deficit: other - self,
+                    })
+                }
+
+                fn try_next_multiple_of(self, rhs: Self) -> Result<Self, NextMultipleError<Self>> {
+                    if rhs == 0 {
+                        return Err(NextMultipleError::ZeroMultiple { value: self });
+                    }
+                    self.checked_next_multiple_of(rhs).ok_or(NextMultipleError::Overflow { value: self, rhs })
+                }
+
+                fn try_next_power_of_two(self) -> Result<Self, NextPowerOfTwoError<Self>> {
+                    self.checked_next_power_of_two().ok_or(NextPowerOfTwoError(self))
+                }
+
+                fn try_to_signed(self) -> Result<Self::Signed, SignedOverflowError<Self>> {
+                    self.try_into().map_err(|_| SignedOverflowError(self))
+                }
+
+                fn signed_diff(self, other: Self) -> Result<Self::Signed, SignedDiffError<Self>> {
+                    let diff = if self >= other {
+                        (0 as $signed).checked_add_unsigned(self - other)
+                    } else {
+                        (0 as $signed).checked_sub_unsigned(other - self)
+                    };
+                    diff.ok_or(SignedDiffError {
+                        left: self,
+                        right: other,
+                    })
+                }
+            }
+        )*
+    }
+}
+
+impl_arith_op!(unsigned: u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize);
+impl_arith_op!(signed: i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_type_names!(f32, f64, bool, char);
+impl_type_names!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize);
+impl_type_names!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize);
+impl_unsigned_op!(u8 => i8, u16 => i16, u32 => i32, u64 => i64, u128 => i128, usize => isize);
+
+macro_rules! impl_signed_op {
+    ($($type:ty),*) => {
+        $(
+            impl SignedTryOps for $type {
+                fn try_to_unsigned(self) -> Result<Self::Unsigned, NegativeError<Self>> {
+                    self.try_into().map_err(|_| NegativeError(self))
+                }
+            }
+        )*
+    }
+}
+
+impl_signed_op!(i8, i16, i32, i64, i128, isize);
+
+/// Computes `a * b % modulus` for `a, b < modulus` without overflowing
+fn mul_mod(a: u128, b: u128, modulus: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % modulus;
+    }
+    // double-and-add, all intermediate values stay below modulus
+    let add_mod = |x: u128, y: u128| if x >= modulus - y { x - (modulus - y) } else { x + y };
+    let (mut a, mut b) = (a, b);
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a);
+        }
+        a = add_mod(a, a);
+        b >>= 1;
+    }
+    result
+}
+
+macro_rules! impl_overflowing_try_op {
+    ($try_op:ident, $overflowing_op:ident, $op_str:expr) => {
+        fn $try_op(self, other: Self) -> Result<Self, WrappedOverflowError<Self>> {
+            match self.$overflowing_op(other) {
+                (result, false) => Ok(result),
+                (wrapped, true) => Err(WrappedOverflowError {
+                    wrapped,
+                    error: OverflowError {
+                        left: self,
+                        op: $op_str,
+                        right: other,
+                    },
+                }),
+            }
+        }
+    }
+}
+
+macro_rules! impl_overflowing_try_ops {
+    ($($type:ty),*) => {
+        $(
+            impl OverflowingTryOps for $type {
+                impl_overflowing_try_op!(try_add_overflowing, overflowing_add, "+");
+                impl_overflowing_try_op!(try_sub_overflowing, overflowing_sub, "-");
+                impl_overflowing_try_op!(try_mul_overflowing, overflowing_mul, "*");
+            }
+        )*
+    }
+}
+
+impl_overflowing_try_ops!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_bit_ops {
+    ($($type:ty),*) => {
+        $(
+            impl BitOps for $type {
+                fn leading_zeros(self) -> u32 {
+                    <$type>::leading_zeros(self)
+                }
+
+                fn trailing_zeros(self) -> u32 {
+                    <$type>::trailing_zeros(self)
+                }
+
+                fn count_ones(self) -> u32 {
+                    <$type>::count_ones(self)
+                }
+
+                fn checked_bit(self, index: u32) -> Result<bool, BigShiftError<Self>> {
+                    self.checked_shr(index).map(|shifted| shifted & 1 == 1).ok_or(BigShiftError {
+                        left: self,
+                        op: ">>",
+                        right: index,
+                    })
+                }
+            }
+        )*
+    }
+}
+
+impl_bit_ops!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_num_theory {
+    ($($type:ty),*) => {
+        $(
+            impl NumTheory for $type {
+                fn gcd(self, other: Self) -> Self {
+                    let (mut a, mut b) = (self, other);
+                    while b != 0 {
+                        let rem = a % b;
+                        a = b;
+                        b = rem;
+                    }
+                    a
+                }
+
+                fn try_lcm(self, other: Self) -> Result<Self, OverflowError<Self, Self>> {
+                    if self == 0 || other == 0 {
+                        return Ok(0);
+                    }
+                    (self / self.gcd(other)).try_mul(other).map_err(|_| OverflowError {
+                        left: self,
+                        op: "lcm",
+                        right: other,
+                    })
+                }
+
+                fn try_pow_mod(self, exp: u32, modulus: Self) -> Result<Self, DivisionByZeroError<Self>> {
+                    if modulus == 0 {
+                        return Err(DivisionByZeroError(self));
+                    }
+                    // all values are less than modulus so they fit back into $type
+                    let modulus = modulus as u128;
+                    let mut base = self as u128 % modulus;
+                    let mut exp = exp;
+                    let mut result = 1 % modulus;
+                    while exp > 0 {
+                        if exp & 1 == 1 {
+                            result = mul_mod(result, base, modulus);
+                        }
+                        base = mul_mod(base, base, modulus);
+                        exp >>= 1;
+                    }
+                    Ok(result as $type)
+                }
+            }
+        )*
+    }
+}
+
+impl_num_theory!(u8, u16, u32, u64, u128, usize);
+
+/// Arithmetic with overflow behavior selected by the strategy `S`
+///
+/// Allows writing code once and choosing the behavior using a type parameter:
+///
+/// * `Checked` - returns `Result` with the same errors as `ArithmeticTryOps`
+/// * `Saturating` - clamps the result to the bounds of the type
+/// * `Wrapping` - wraps around at the bounds of the type
+///
+/// Since integers implement this for all strategies, the method calls may be ambiguous. Call them
+/// as `IntOps::<S>::op_add(a, b)` in such case.
+pub trait IntOps<S>: Sized {
+    /// `Result` for `Checked`, `Self` otherwise
+    type Output;
+
+    fn op_add(self, other: Self) -> Self::Output;
+    fn op_sub(self, other: Self) -> Self::Output;
+    fn op_mul(self, other: Self) -> Self::Output;
+}
+
+/// `IntOps` strategy returning errors on overflow
+pub enum Checked {}
+
+/// `IntOps` strategy clamping the result to the bounds of the type
+pub enum Saturating {}
+
+/// `IntOps` strategy wrapping around at the bounds of the type
+pub enum Wrapping {}
+
+impl<T: ArithmeticTryOps> IntOps<Checked> for T {
+    type Output = Result<T, OverflowError<T, T>>;
+
+    fn op_add(self, other: Self) -> Self::Output {
+        self.try_add(other)
+    }
+
+    fn op_sub(self, other: Self) -> Self::Output {
+        self.try_sub(other)
+    }
+
+    fn op_mul(self, other: Self) -> Self::Output {
+        self.try_mul(other)
+    }
+}
+
+macro_rules! impl_int_ops {
+    ($strategy:ty, $add:ident, $sub:ident, $mul:ident: $($type:ty),*) => {
+        $(
+            impl IntOps<$strategy> for $type {
+                type Output = $type;
+
+                fn op_add(self, other: Self) -> Self::Output {
+                    self.$add(other)
+                }
+
+                fn op_sub(self, other: Self) -> Self::Output {
+                    self.$sub(other)
+                }
+
+                fn op_mul(self, other: Self) -> Self::Output {
+                    self.$mul(other)
+                }
+            }
+        )*
+    }
+}
+
+impl_int_ops!(Saturating, saturating_add, saturating_sub, saturating_mul: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_int_ops!(Wrapping, wrapping_add, wrapping_sub, wrapping_mul: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Checked operations on `Duration`
+///
+/// `Duration` is not an integer, so it can't implement `ArithmeticTryOps`. This provides the
+/// subset that makes sense for it. `Duration` doesn't implement `Display`, so it has its own error
+/// types formatting it using `Debug` (e.g. `1.5s`).
+pub trait DurationTryOps: Sized {
+    fn try_add(self, other: Duration) -> Result<Self, DurationOverflowError<Duration>>;
+    fn try_sub(self, other: Duration) -> Result<Self, DurationOverflowError<Duration>>;
+    fn try_mul(self, other: u32) -> Result<Self, DurationOverflowError<u32>>;
+    fn try_div(self, other: u32) -> Result<Self, DurationDivisionByZeroError>;
+}
+
+macro_rules! impl_duration_overflowing_op {
+    ($try_op:ident, $check_op:ident, $rhs:ty, $op_str:expr) => {
+        fn $try_op(self, other: $rhs) -> Result<Self, DurationOverflowError<$rhs>> {
+            self.$check_op(other).ok_or(DurationOverflowError {
+                left: self,
+                op: $op_str,
+                right: other,
+            })
+        }
+    }
+}
+
+impl DurationTryOps for Duration {
+    impl_duration_overflowing_op!(try_add, checked_add, Duration, "+");
+    impl_duration_overflowing_op!(try_sub, checked_sub, Duration, "-");
+    impl_duration_overflowing_op!(try_mul, checked_mul, u32, "*");
+
+    fn try_div(self, other: u32) -> Result<Self, DurationDivisionByZeroError> {
+        self.checked_div(other).ok_or(DurationDivisionByZeroError(self))
+    }
+}
+
+/// Returned from `DurationTryOps` when the result doesn't fit into `Duration`
+#[derive(Debug, thiserror::Error)]
+#[error("operation {left:?} {op} {right:?} overflowed")]
+pub struct DurationOverflowError<R: core::fmt::Debug> {
+    left: Duration,
+    op: &'static str,
+    right: R,
+}
+
+/// Returned from `DurationTryOps::try_div` when the divisor is zero
+#[derive(Debug, thiserror::Error)]
+#[error("attempted to divide {0:?} by zero")]
+pub struct DurationDivisionByZeroError(Duration);
+
+impl TypeName for Duration {
+    fn type_name() -> &'static str {
+        "Duration"
+    }
+}
+
+/// Checked codepoint arithmetic on `char`
+///
+/// Useful for iterating alphabets or Caesar-cipher-style transforms. The result must be a valid
+/// Unicode scalar value - operations landing in the surrogate range (`U+D800..=U+DFFF`) or beyond
+/// `U+10FFFF` fail. Crossing the surrogate range is fine.
+pub trait CharTryOps: Sized {
+    fn try_add(self, other: u32) -> Result<Self, CharRangeError>;
+    fn try_sub(self, other: u32) -> Result<Self, CharRangeError>;
+}
+
+impl CharTryOps for char {
+    fn try_add(self, other: u32) -> Result<Self, CharRangeError> {
+        (self as u32).checked_add(other)
+            .and_then(char::from_u32)
+            .ok_or(CharRangeError { left: self, op: "+", right: other })
+    }
+
+    fn try_sub(self, other: u32) -> Result<Self, CharRangeError> {
+        (self as u32).checked_sub(other)
+            .and_then(char::from_u32)
+            .ok_or(CharRangeError { left: self, op: "-", right: other })
+    }
+}
+
+/// Returned when codepoint arithmetic doesn't result in a valid `char`
+#[derive(Debug, thiserror::Error)]
+#[error("operation {left:?} {op} {right} doesn't result in a valid char (the codepoint is a surrogate or out of range)")]
+pub struct CharRangeError {
+    left: char,
+    op: &'static str,
+    right: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArithmeticTryOps, ArithmeticTryOpsRef, DivisionError, SignedTryOps, TryArithIterExt, UnsignedTryOps, try_horner};
+
+    #[test]
+    fn add() {
+        assert!(255u8.try_add(1).is_err());
+    }
+
+    #[test]
+    fn overflow_message_contains_type() {
+        assert_eq!(200u8.try_pow(2).unwrap_err().to_string(), "operation 200 ** 2 overflowed (the type of LHS is u8)");
+        // the message of other operations is unchanged
+        assert_eq!(255u8.try_add(1).unwrap_err().to_string(), "operation 255 + 1 overflowed");
+    }
+
+    #[test]
+    fn incr_mod() {
+        assert_eq!(3u8.try_incr_mod(5).unwrap(), 4);
+        assert_eq!(4u8.try_incr_mod(5).unwrap(), 0);
+        assert_eq!(254u8.try_incr_mod(255).unwrap(), 0);
+        assert_eq!(255u8.try_incr_mod(255).unwrap(), 1);
+        assert!(4u8.try_incr_mod(0).is_err());
+    }
+
+    #[test]
+    fn div_rem() {
+        assert_eq!(17u8.try_div_rem(5).unwrap(), (3, 2));
+        assert_eq!((-17i8).try_div_rem(5).unwrap(), (-3, -2));
+        assert!(17u8.try_div_rem(0).is_err());
+        assert_eq!(17i8.try_div_rem(0).unwrap_err().to_string(), "attempted to divide 17 by zero");
+        assert_eq!(i8::MIN.try_div_rem(-1).unwrap_err().to_string(), "operation -128 / -1 overflowed");
+        assert!(matches!(i8::MIN.try_div_rem(-1), Err(DivisionError::Overflow(_))));
+        assert!(matches!(i8::MIN.try_div_rem(0), Err(DivisionError::DivisionByZero(_))));
+    }
+
+    #[test]
+    fn horner() {
+        // 2x^2 + 3x + 4
+        assert_eq!(try_horner(&[2u32, 3, 4], 5).unwrap(), 69);
+        assert_eq!(try_horner::<u32>(&[], 5).unwrap(), 0);
+        let error = try_horner(&[1u8, 0, 0, 0], 10).unwrap_err();
+        assert_eq!(error.to_string(), "operation 100 * 10 overflowed");
+    }
+
+    #[test]
+    fn leb128_len() {
+        assert_eq!(0u32.leb128_len(), 1);
+        assert_eq!(127u32.leb128_len(), 1);
+        assert_eq!(128u32.leb128_len(), 2);
+        assert_eq!(u64::MAX.leb128_len(), 10);
+    }
+
+    #[test]
+    fn ops_on_references() {
+        fn sum<T: ArithmeticTryOps + Copy>(a: &T, b: &T) -> T {
+            a.try_add_ref(b).unwrap()
+        }
+
+        fn double<T: ArithmeticTryOps + Copy>(a: &T) -> T {
+            a.try_add(*a).unwrap()
+        }
+
+        assert_eq!(sum(&2u8, &3), 5);
+        assert_eq!(double(&2u8), 4);
+    }
+
+    #[test]
+    fn isqrt() {
+        assert_eq!(17u8.try_isqrt().unwrap(), 4);
+        assert_eq!(16i32.try_isqrt().unwrap(), 4);
+        assert_eq!((-4i32).try_isqrt().unwrap_err().to_string(), "cannot take sqrt of negative value -4");
+    }
+
+    #[test]
+    fn shift_message_contains_width() {
+        assert_eq!(1i32.try_shl(40).unwrap_err().to_string(), "operation 1 << 40 failed: shift amount must be less than 32 (the width of i32)");
+        assert!(1i32.try_shr(31).is_ok());
+    }
+
+    #[test]
+    fn iter_sum_product() {
+        assert_eq!([1u8, 2, 3].iter().copied().try_sum().unwrap(), 6);
+        assert_eq!(core::iter::empty::<u8>().try_sum().unwrap(), 0);
+        assert_eq!([200u8, 50, 10].iter().copied().try_sum().unwrap_err().to_string(), "operation 250 + 10 overflowed");
+        assert_eq!([2u8, 3, 4].iter().copied().try_product().unwrap(), 24);
+        assert_eq!(core::iter::empty::<u8>().try_product().unwrap(), 1);
+        assert!([16u8, 16].iter().copied().try_product().is_err());
+    }
+
+    #[test]
+    fn duration() {
+        use super::DurationTryOps;
+        use core::time::Duration;
+
+        assert_eq!(Duration::from_secs(1).try_add(Duration::from_secs(2)).unwrap(), Duration::from_secs(3));
+        assert_eq!(Duration::from_secs(1).try_sub(Duration::from_secs(2)).unwrap_err().to_string(), "operation 1s - 2s overflowed");
+        assert!(Duration::MAX.try_mul(2).is_err());
+        assert_eq!(Duration::from_secs(1).try_div(0).unwrap_err().to_string(), "attempted to divide 1s by zero");
+    }
+
+    #[test]
+    fn abs_diff() {
+        fn generic<T: ArithmeticTryOps>(a: T, b: T) -> T::Unsigned {
+            ArithmeticTryOps::abs_diff(a, b)
+        }
+
+        assert_eq!(generic(3u8, 200), 197);
+        assert_eq!(generic(i8::MIN, i8::MAX), 255u8);
+    }
+
+    #[test]
+    fn arithmetic_error() {
+        use super::ArithmeticError;
+
+        fn compute(a: u8, b: u8) -> Result<u8, ArithmeticError<u8, u8>> {
+            Ok(a.try_div(b)?.try_add(b)?)
+        }
+
+        assert_eq!(compute(10, 2).unwrap(), 7);
+        assert_eq!(compute(10, 0).unwrap_err().to_string(), "attempted to divide 10 by zero");
+        assert_eq!(compute(255, 1).unwrap_err().to_string(), "operation 255 + 1 overflowed");
+    }
+
+    #[test]
+    fn modulo() {
+        assert_eq!((-7i32).try_mod(3).unwrap(), 2);
+        assert_eq!((-7i32).try_mod(-3).unwrap(), 2);
+        assert_eq!((-7i32).try_rem(3).unwrap(), -1);
+        assert_eq!(7u32.try_mod(3).unwrap(), 1);
+        assert!((-7i32).try_mod(0).is_err());
+    }
+
+    #[test]
+    fn assign() {
+        use super::ArithmeticTryAssign;
+
+        let mut value = 250u8;
+        value.try_add_assign(5).unwrap();
+        assert_eq!(value, 255);
+        assert_eq!(value.try_add_assign(1).unwrap_err().to_string(), "operation 255 + 1 overflowed");
+        assert_eq!(value, 255);
+        value.try_shr_assign(4).unwrap();
+        assert_eq!(value, 15);
+    }
+
+    #[test]
+    fn custom_type() {
+        use super::{BitWidth, TypeName};
+
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        struct Meters(i32);
+
+        macro_rules! delegate {
+            ($($method:ident($($arg:ident: $arg_ty:ty),*)),*) => {
+                $(
+                    fn $method(self, $($arg: $arg_ty),*) -> Option<Self> {
+                        self.0.$method($($arg.into_inner()),*).map(Meters)
+                    }
+                )*
+            }
+        }
+
+        trait IntoInner {
+            type Inner;
+            fn into_inner(self) -> Self::Inner;
+        }
+
+        impl IntoInner for Meters {
+            type Inner = i32;
+            fn into_inner(self) -> i32 { self.0 }
+        }
+
+        impl IntoInner for u32 {
+            type Inner = u32;
+            fn into_inner(self) -> u32 { self }
+        }
+
+        impl Meters {
+            delegate!(checked_add(other: Self), checked_sub(other: Self), checked_mul(other: Self), checked_div(other: Self), checked_rem(other: Self), checked_div_euclid(other: Self), checked_rem_euclid(other: Self), checked_pow(exp: u32), checked_shl(amount: u32), checked_shr(amount: u32), checked_isqrt());
+
+            fn checked_div_ceil(self, other: Self) -> Option<Self> {
+                let quotient = self.0.checked_div(other.0)?;
+                let remainder = self.0 % other.0;
+                Some(Meters(if remainder != 0 && (remainder > 0) == (other.0 > 0) { quotient + 1 } else { quotient }))
+            }
+
+            fn abs_diff(self, other: Self) -> u32 {
+                self.0.abs_diff(other.0)
+            }
+
+            fn midpoint(self, other: Self) -> Self {
+                Meters(self.0.try_midpoint(other.0))
+            }
+        }
+
+        impl core::fmt::Display for Meters {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "{}m", self.0)
+            }
+        }
+
+        impl TypeName for Meters {
+            fn type_name() -> &'static str {
+                "Meters"
+            }
+        }
+
+        impl BitWidth for Meters {
+            const BIT_WIDTH: u32 = 32;
+        }
+
+        impl_try_ops_from_checked!(Meters => u32);
+
+        assert_eq!(Meters(1).try_add(Meters(2)).unwrap(), Meters(3));
+        assert!(Meters(i32::MAX).try_add(Meters(1)).is_err());
+        assert!(Meters(1).try_div(Meters(0)).is_err());
+        assert!(matches!(Meters(1).try_div_rem(Meters(0)), Err(DivisionError::DivisionByZero(_))));
+        assert!(matches!(Meters(i32::MIN).try_div_rem(Meters(-1)), Err(DivisionError::Overflow(_))));
+        assert_eq!(ArithmeticTryOps::abs_diff(Meters(-1), Meters(1)), 2);
+    }
+
+    #[test]
+    fn gcd_lcm() {
+        use super::NumTheory;
+
+        assert_eq!(12u32.gcd(18), 6);
+        assert_eq!(0u32.gcd(0), 0);
+        assert_eq!(0u32.gcd(5), 5);
+        assert_eq!(4u32.try_lcm(6).unwrap(), 12);
+        assert_eq!(0u32.try_lcm(6).unwrap(), 0);
+        assert_eq!(200u8.try_lcm(3).unwrap_err().to_string(), "operation 200 lcm 3 overflowed");
+    }
+
+    #[test]
+    fn mul_add() {
+        assert_eq!(10u8.try_mul_add(20, 55).unwrap(), 255);
+        assert_eq!(10u8.try_mul_add(20, 56).unwrap_err().to_string(), "operation 200 + 56 overflowed");
+        assert_eq!(10u8.try_mul_add(26, 0).unwrap_err().to_string(), "operation 10 * 26 overflowed");
+    }
+
+    #[test]
+    fn pow_any() {
+        assert_eq!(2u8.try_pow_any(7usize).unwrap(), 128);
+        assert_eq!(2u8.try_pow_any(-1i64).unwrap_err().to_string(), "the exponent for base 2 is out of range of u32");
+        assert_eq!(2u8.try_pow_any(u64::MAX).unwrap_err().to_string(), "the exponent for base 2 is out of range of u32");
+        assert_eq!(2u8.try_pow_any(8u64).unwrap_err().to_string(), "operation 2 ** 8 overflowed (the type of LHS is u8)");
+    }
+
+    #[test]
+    fn widening_rhs() {
+        assert_eq!(100u64.try_add_any(5u32).unwrap(), 105);
+        assert_eq!(100u64.try_sub_any(5u8).unwrap(), 95);
+        assert_eq!(100i64.try_mul_any(-5i32).unwrap(), -500);
+        assert_eq!(100u16.try_div_any(0u8).unwrap_err().to_string(), "attempted to divide 100 by zero");
+        assert_eq!(u64::MAX.try_add_any(1u32).unwrap_err().to_string(), "operation 18446744073709551615 + 1 overflowed");
+        // literals are still inferred
+        assert_eq!(2u64.try_add(3).unwrap(), 5);
+    }
+
+    #[test]
+    fn sign_conversion() {
+        assert_eq!(42i32.try_to_unsigned().unwrap(), 42u32);
+        assert_eq!((-1i32).try_to_unsigned().unwrap_err().to_string(), "cannot convert negative value -1 of type i32 to u32");
+        assert_eq!(127u8.try_to_signed().unwrap(), 127i8);
+        assert_eq!(128u8.try_to_signed().unwrap_err().to_string(), "value 128 of type u8 doesn't fit into i8");
+    }
+
+    #[test]
+    fn sub_or_deficit() {
+        assert_eq!(10u32.try_sub_or_deficit(3).unwrap(), 7);
+        let error = 3u32.try_sub_or_deficit(10).unwrap_err();
+        assert_eq!(error.deficit(), 7);
+        assert_eq!(error.to_string(), "operation 3 - 10 underflowed by 7 (the type of LHS is u32)");
+    }
+
+    #[test]
+    fn div_ceil() {
+        assert_eq!(7u8.try_div_ceil(2).unwrap(), 4);
+        assert_eq!(8u8.try_div_ceil(2).unwrap(), 4);
+        assert_eq!(u8::MAX.try_div_ceil(2).unwrap(), 128);
+        assert_eq!(7i32.try_div_ceil(2).unwrap(), 4);
+        assert_eq!((-7i32).try_div_ceil(2).unwrap(), -3);
+        assert_eq!(7i32.try_div_ceil(-2).unwrap(), -3);
+        assert_eq!((-7i32).try_div_ceil(-2).unwrap(), 4);
+        assert_eq!(i32::MIN.try_div_ceil(1).unwrap(), i32::MIN);
+        assert_eq!(1u8.try_div_ceil(0).unwrap_err().to_string(), "attempted to divide 1 by zero");
+        assert!(matches!(1i8.try_div_ceil(0), Err(DivisionError::DivisionByZero(_))));
+        assert_eq!(i8::MIN.try_div_ceil(-1).unwrap_err().to_string(), "operation -128 div_ceil -1 overflowed");
+    }
+
+    #[test]
+    fn shift_any() {
+        assert_eq!(1u32.try_shl_any(4usize).unwrap(), 16);
+        assert_eq!(16u32.try_shr_any(4u64).unwrap(), 1);
+        assert_eq!(1u32.try_shl_any(32usize).unwrap_err().to_string(), "operation 1 << 32 failed: shift amount must be less than 32 (the width of u32)");
+        assert_eq!(1u32.try_shr_any(u64::MAX).unwrap_err().to_string(), "operation 1 >> 18446744073709551615 failed: shift amount must be less than 32 (the width of u32)");
+        assert!(1u32.try_shl_any(-1i32).is_err());
+    }
+
+    #[test]
+    fn pow_mod() {
+        use super::NumTheory;
+
+        assert_eq!(4u32.try_pow_mod(13, 497).unwrap(), 445);
+        assert_eq!(2u8.try_pow_mod(0, 1).unwrap(), 0);
+        assert_eq!(200u8.try_pow_mod(5, 251).unwrap(), (200u64.pow(5) % 251) as u8);
+        assert_eq!(u64::MAX.try_pow_mod(2, u64::MAX - 1).unwrap(), 1);
+        // (2^127 - 2)^2 mod (2^127 - 1) = 1
+        let m = (1u128 << 127) - 1;
+        assert_eq!((m - 1).try_pow_mod(2, m).unwrap(), 1);
+        assert_eq!(u128::MAX.try_pow_mod(3, u128::MAX - 1).unwrap(), 1);
+        assert_eq!(2u32.try_pow_mod(3, 0).unwrap_err().to_string(), "attempted to divide 2 by zero");
+    }
+
+    #[test]
+    fn type_names() {
+        use super::TypeName;
+
+        assert_eq!(f64::type_name(), "f64");
+        assert_eq!(bool::type_name(), "bool");
+        assert_eq!(core::num::NonZeroU32::type_name(), "NonZeroU32");
+    }
+
+    #[test]
+    fn int_ops() {
+        use super::{Checked, IntOps, Saturating, Wrapping};
+
+        fn add<S>(a: u8, b: u8) -> <u8 as IntOps<S>>::Output where u8: IntOps<S> {
+            IntOps::<S>::op_add(a, b)
+        }
+
+        assert_eq!(add::<Saturating>(200, 100), 255);
+        assert_eq!(add::<Wrapping>(200, 100), 44);
+        assert_eq!(add::<Checked>(200, 50).unwrap(), 250);
+        assert!(add::<Checked>(200, 100).is_err());
+        assert_eq!(IntOps::<Saturating>::op_sub(-100i8, 100), -128);
+    }
+
+    #[test]
+    fn overflowing() {
+        use super::OverflowingTryOps;
+
+        assert_eq!(200u8.try_add_overflowing(50).unwrap(), 250);
+        let error = 200u8.try_add_overflowing(100).unwrap_err();
+        assert_eq!(error.wrapped(), 44);
+        assert_eq!(error.to_string(), "operation 200 + 100 overflowed");
+        assert_eq!(0u8.try_sub_overflowing(1).unwrap_err().wrapped(), 255);
+        assert_eq!(i8::MIN.try_mul_overflowing(-1).unwrap_err().into_parts().0, i8::MIN);
+    }
+
+    #[test]
+    fn next_multiple_of() {
+        assert_eq!(13u32.try_next_multiple_of(8).unwrap(), 16);
+        assert_eq!(16u32.try_next_multiple_of(8).unwrap(), 16);
+        assert_eq!(0u32.try_next_multiple_of(8).unwrap(), 0);
+        assert_eq!(250u8.try_next_multiple_of(5).unwrap(), 250);
+        assert_eq!(251u8.try_next_multiple_of(10).unwrap_err().to_string(), "rounding 251 up to a multiple of 10 overflowed (the type is u8)");
+        assert_eq!(3u8.try_next_multiple_of(0).unwrap_err().to_string(), "cannot round 3 up to a multiple of zero");
+    }
+
+    #[test]
+    fn next_power_of_two() {
+        assert_eq!(5u8.try_next_power_of_two().unwrap(), 8);
+        assert_eq!(0u8.try_next_power_of_two().unwrap(), 1);
+        assert_eq!(128u8.try_next_power_of_two().unwrap(), 128);
+        assert_eq!(u8::MAX.try_next_power_of_two().unwrap_err().to_string(), "the smallest power of two greater than or equal to 255 overflows u8");
+    }
+
+    #[test]
+    fn sum_into() {
+        let items = [200u8, 200, 200];
+        assert!(items.iter().copied().try_sum().is_err());
+        assert_eq!(items.iter().copied().try_sum_into::<u32>().unwrap(), 600);
+        assert!([u32::MAX, 1].iter().copied().try_sum_into::<u32>().is_err());
+    }
+
+    #[test]
+    fn clamp() {
+        assert_eq!(5u8.try_clamp(1, 3).unwrap(), 3);
+        assert_eq!((-5i8).try_clamp(-3, 3).unwrap(), -3);
+        assert_eq!(2i8.try_clamp(-3, 3).unwrap(), 2);
+        assert_eq!(2i8.try_clamp(3, -3).unwrap_err().to_string(), "invalid bounds: minimum 3 is greater than maximum -3");
+    }
+
+    #[test]
+    fn rem_euclid_is_nonneg_residue() {
+        for a in -20i32..=20 {
+            for m in 1i32..=7 {
+                assert_eq!(a.try_rem_euclid(m).unwrap(), ((a % m) + m) % m);
+                assert_eq!(a.try_mod(m).unwrap(), ((a % m) + m) % m);
+                assert_eq!(a.wrapping_rem_nonneg(m).unwrap(), ((a % m) + m) % m);
+            }
+        }
+        assert_eq!((-7i32).try_rem_euclid(-3).unwrap(), 2);
+        assert_eq!((-7i32).wrapping_rem_nonneg(-3).unwrap(), 2);
+        assert_eq!(7i32.wrapping_rem_nonneg(-3).unwrap(), 1);
+        assert!((-7i32).wrapping_rem_nonneg(0).is_err());
+        // the idiom would overflow here
+        assert_eq!(126i8.try_rem_euclid(i8::MAX).unwrap(), 126);
+    }
+
+    #[test]
+    fn signed_diff() {
+        assert_eq!(10u8.signed_diff(3).unwrap(), 7i8);
+        assert_eq!(3u8.signed_diff(10).unwrap(), -7i8);
+        assert_eq!(0u8.signed_diff(128).unwrap(), i8::MIN);
+        assert_eq!(127u8.signed_diff(0).unwrap(), i8::MAX);
+        assert_eq!(128u8.signed_diff(0).unwrap_err().to_string(), "difference 128 - 0 doesn't fit into i8");
+        assert_eq!(0u64.signed_diff(u64::MAX).unwrap_err().to_string(), "difference 0 - 18446744073709551615 doesn't fit into i64");
+    }
+
+    #[test]
+    fn midpoint() {
+        fn midpoint<T: ArithmeticTryOps>(a: T, b: T) -> T {
+            a.try_midpoint(b)
+        }
+
+        assert_eq!(u8::MAX.try_midpoint(u8::MAX), u8::MAX);
+        assert_eq!(midpoint(u8::MAX, u8::MAX), u8::MAX);
+        assert_eq!(midpoint(u8::MAX, 0), 127);
+        assert_eq!(midpoint(i8::MAX, i8::MAX), i8::MAX);
+        assert_eq!(midpoint(i8::MIN, i8::MIN), i8::MIN);
+        assert_eq!(midpoint(i8::MIN, i8::MAX), 0);
+        assert_eq!(midpoint(-1i32, 0), 0);
+        assert_eq!(midpoint(-3i32, 0), -1);
+        assert_eq!(midpoint(1i32, 2), 1);
+        for a in -20i32..=20 {
+            for b in -20i32..=20 {
+                assert_eq!(midpoint(a, b), (a + b) / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn char_ops() {
+        use super::CharTryOps;
+
+        assert_eq!('a'.try_add(2).unwrap(), 'c');
+        assert_eq!('c'.try_sub(2).unwrap(), 'a');
+        assert_eq!('\u{D7FF}'.try_add(0x801).unwrap(), '\u{E000}');
+        assert_eq!('\u{D7FF}'.try_add(1).unwrap_err().to_string(), "operation '\\u{d7ff}' + 1 doesn't result in a valid char (the codepoint is a surrogate or out of range)");
+        assert!('\u{E000}'.try_sub(1).is_err());
+        assert!(char::MAX.try_add(1).is_err());
+        assert!('a'.try_sub(0x62).is_err());
+    }
+
+    #[test]
+    fn bit_ops() {
+        use super::BitOps;
+
+        fn count<T: BitOps>(value: T) -> u32 {
+            value.count_ones()
+        }
+
+        assert_eq!(count(0b1011u8), 3);
+        assert!(0b100u8.checked_bit(2).unwrap());
+        assert!(!0b100u8.checked_bit(1).unwrap());
+        assert!((-1i8).checked_bit(7).unwrap());
+        assert_eq!(5u8.checked_bit(8).unwrap_err().to_string(), "operation 5 >> 8 failed: shift amount must be less than 8 (the width of u8)");
     }
 }
@@ -0,0 +1,57 @@
+use crate::ops::TypeName;
+
+/// Integer types that can be parsed in a given radix
+pub trait FromStrRadix: TypeName + Sized {
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, core::num::ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($type:ty),*) => {
+        $(
+            impl FromStrRadix for $type {
+                fn from_str_radix(s: &str, radix: u32) -> Result<Self, core::num::ParseIntError> {
+                    <$type>::from_str_radix(s, radix)
+                }
+            }
+        )*
+    }
+}
+
+impl_from_str_radix!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Parses integer in given radix returning an error that mentions the input, type and radix
+///
+/// Panics if `radix` is not in the range 2..=36, just like `from_str_radix`.
+pub fn parse_radix<T: FromStrRadix>(s: &str, radix: u32) -> Result<T, ParseError> {
+    T::from_str_radix(s, radix).map_err(|error| ParseError {
+        input: s.to_owned(),
+        type_name: T::type_name(),
+        radix,
+        error,
+    })
+}
+
+/// Returned from `parse_radix()`
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("failed to parse '{input}' as {type_name} in base {radix}")]
+pub struct ParseError {
+    input: String,
+    type_name: &'static str,
+    radix: u32,
+    #[source]
+    error: core::num::ParseIntError,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_radix;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_radix::<u8>("ff", 16).unwrap(), 255);
+        assert_eq!(parse_radix::<i32>("-101", 2).unwrap(), -5);
+        let error = parse_radix::<u8>("1ff", 16).unwrap_err();
+        assert_eq!(error.to_string(), "failed to parse '1ff' as u8 in base 16");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
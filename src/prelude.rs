@@ -0,0 +1,18 @@
+//! Extension traits for glob importing
+//!
+//! Most of the functionality of this crate is provided as trait methods which only work when the
+//! trait is in scope. `use random_stuff::prelude::*;` brings all of them in. Only traits are
+//! exported. (Explicit imports shadow glob imports, so importing e.g. `log::Log` is fine too.)
+//!
+//! Some method names are shared by several of the traits: `try_add` and `try_sub` exist in
+//! `ArithmeticTryOps`, `CharTryOps` and `DurationTryOps`. Those are implemented for disjoint types
+//! so method calls still resolve, but generic code bounded by more than one of them has to use the
+//! `Trait::method(value)` syntax. `abs_diff` and the `BitOps` counting methods have the same names
+//! as inherent methods of integers - calling them on a concrete integer calls the inherent method
+//! which behaves the same.
+
+pub use crate::ops::{ArithmeticTryAssign, ArithmeticTryOps, ArithmeticTryOpsRef, BitOps, CharTryOps, DurationTryOps, NumTheory, OverflowingTryOps, SignedTryOps, TryArithIterExt, UnsignedTryOps};
+#[cfg(feature = "std")]
+pub use crate::error::DisplayError;
+#[cfg(feature = "std")]
+pub use crate::result::{IntoResult, Log, LogOwned, LogResult, OptionExt, UnwrapOrExit};
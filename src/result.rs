@@ -1,4 +1,4 @@
-use crate::error::DisplayError;
+use crate::error::{BoxedError, ContextError, DisplayError, ErrorSnapshot, StringError};
 
 /// Helper making implementations shorter
 pub trait IntoResult: Sized {
@@ -10,13 +10,35 @@ pub trait IntoResult: Sized {
 
     /// A version of map_err that doesn't consume error
     fn with_err<F: FnOnce(&Self::Error)>(self, fun: F) -> Result<Self::Value, Self::Error> {
-        self.internal_into_result().map_err(|error| { fun(&error); error })
+        let result = self.internal_into_result();
+        if let Err(error) = &result {
+            fun(error);
+        }
+        result
+    }
+
+    /// Converts the error into `BoxedError`
+    ///
+    /// Handy for funneling heterogeneous errors into a single type.
+    fn map_err_boxed(self) -> Result<Self::Value, BoxedError> where Self::Error: 'static + std::error::Error {
+        self.internal_into_result().map_err(BoxedError::new)
+    }
+
+    /// Wraps the error in `ContextError` displaying `context`
+    ///
+    /// The original error is kept as `source()`, so `join_sources` displays `context: error`.
+    fn context<C: std::fmt::Display + std::fmt::Debug>(self, context: C) -> Result<Self::Value, ContextError<C>> where Self::Error: 'static + std::error::Error {
+        self.internal_into_result().map_err(|error| ContextError::new(context, error))
     }
 }
 
 /// ResultExt that provides nicer error messages than unwrap/expect
 ///
 /// Exits with exit code 2 to allow grep-like behavior
+///
+/// Note that exiting doesn't run destructors, so buffered writers owned by the application are
+/// **not** flushed. Standard output and error are flushed. If you need destructors to run, return
+/// `ExitCode` from `main()` instead.
 pub trait UnwrapOrExit: IntoResult {
     /// Another trick to shorten impl
     ///
@@ -24,7 +46,18 @@ pub trait UnwrapOrExit: IntoResult {
     fn unwrap_or_exit_custom<F: FnOnce(Self::Error)>(self, printer: F) -> Self::Value {
         self.internal_into_result().unwrap_or_else(|error| {
             printer(error);
-            std::process::exit(2);
+            flush_std_and_exit();
+        })
+    }
+
+    /// Same as `unwrap_or_exit_custom` but runs `cleanup` before exiting
+    ///
+    /// The order is: print, then cleanup, then exit. Useful for flushing files or releasing locks
+    /// since `exit` doesn't run destructors. On `Ok` the cleanup is **not** run.
+    fn unwrap_or_exit_cleanup<F: FnOnce(Self::Error), C: FnOnce()>(self, cleanup: C, printer: F) -> Self::Value {
+        self.unwrap_or_exit_custom(|error| {
+            printer(error);
+            cleanup();
         })
     }
 
@@ -37,6 +70,41 @@ pub trait UnwrapOrExit: IntoResult {
         })
     }
 
+    /// Same as `unwrap_or_exit` but prepends context to the error
+    ///
+    /// Prints `Error: {context}: {error}` with sources separated by `: `.
+    fn expect_or_exit(self, context: &str) -> Self::Value where Self::Error: 'static + std::error::Error {
+        self.unwrap_or_exit_custom(|error| {
+            eprintln!("Error: {}: {}", context, error.join_sources(": "));
+        })
+    }
+
+    /// Same as `unwrap_or_exit` but prints a hint on the next line
+    ///
+    /// Useful to suggest a fix to the user, e.g. `Hint: run `myapp init` first`.
+    fn unwrap_or_exit_hint(self, hint: &str) -> Self::Value where Self::Error: 'static + std::error::Error {
+        self.unwrap_or_exit_custom(|error| {
+            eprintln!("Error: {}", error.join_sources(": "));
+            eprintln!("Hint: {}", hint);
+        })
+    }
+
+    /// Prints the error as JSON object to stderr
+    ///
+    /// The output looks like `{"error":"top-level error","causes":["source","source of source"]}`,
+    /// same as `error::JsonTerminator`. Intended for tools invoked by other programs.
+    #[cfg(feature = "serde_json")]
+    fn unwrap_or_exit_json(self) -> Self::Value where Self::Error: 'static + std::error::Error {
+        use crate::error::{JsonTerminator, TerminationInfo};
+
+        self.unwrap_or_exit_custom(|error| {
+            let mut json = String::new();
+            // writing into String can't fail
+            let _ = JsonTerminator::write_error(&mut json, &error);
+            eprintln!("{}", json);
+        })
+    }
+
     /// Formatting using Display
     fn unwrap_or_exit_display(self) -> Self::Value where Self::Error: std::fmt::Display {
         self.unwrap_or_exit_custom(|error| eprintln!("Error: {}", error))
@@ -51,6 +119,69 @@ pub trait UnwrapOrExit: IntoResult {
     fn unwrap_or_exit_log<L: LogOwned>(self, mut logger: L) -> Self::Value where Self::Error: 'static + std::error::Error {
         self.unwrap_or_exit_custom(|error| logger.log_error_owned("Error", error))
     }
+
+    /// Log error that only implements `Display` and exit
+    ///
+    /// The error is formatted into `StringError` first, so this allocates and the logger doesn't
+    /// see the original type. Display-only errors don't have `source()` so there's no chain to log.
+    fn unwrap_or_exit_log_display<L: LogOwned>(self, mut logger: L) -> Self::Value where Self::Error: std::fmt::Display {
+        self.unwrap_or_exit_custom(|error| logger.log_error_owned("Error", StringError(error.to_string())))
+    }
+}
+
+/// Prints the error with sources separated by `: `, flushes stdout and stderr and exits with code 2
+///
+/// This is the same as what `UnwrapOrExit::unwrap_or_exit` does on error.
+pub fn report_and_exit(error: &(dyn 'static + std::error::Error)) -> ! {
+    eprintln!("Error: {}", error.join_sources(": "));
+    flush_std_and_exit()
+}
+
+/// Converts the result to `ExitCode`, printing the error if there is one
+///
+/// On error prints `Error: ` followed by the sources separated by `: ` and returns exit code 2,
+/// same as `UnwrapOrExit::unwrap_or_exit`. Unlike it, this doesn't exit, so destructors still run.
+/// Intended to be returned from `main()`.
+pub fn exit_code_from<T, E: 'static + std::error::Error>(result: Result<T, E>) -> std::process::ExitCode {
+    match result {
+        Ok(_) => std::process::ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error.join_sources(": "));
+            std::process::ExitCode::from(2)
+        },
+    }
+}
+
+/// Runs `f` and if it panics prints the panic message and exits with code 2
+///
+/// Useful at the CLI boundary when calling libraries that panic instead of returning errors.
+/// Uses `std::panic::catch_unwind`, so the limitations apply: it does nothing if panics abort
+/// (`panic = "abort"`) and the panic hook still runs, so by default the message is printed twice
+/// (once by the hook, once by this function). Install a custom hook if you want to avoid it.
+pub fn catch_unwind_or_exit<F, T>(f: F) -> T where F: FnOnce() -> T + std::panic::UnwindSafe {
+    std::panic::catch_unwind(f).unwrap_or_else(|payload| {
+        eprintln!("Error: panicked: {}", panic_message(&*payload));
+        flush_std_and_exit()
+    })
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&'static str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+fn flush_std_and_exit() -> ! {
+    use std::io::Write;
+
+    // we're exiting anyway, nothing to do about errors
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    std::process::exit(2)
 }
 
 impl<T, E> IntoResult for Result<T, E> {
@@ -73,8 +204,7 @@ impl<T, E> UnwrapOrExit for Result<T, E> {}
 /// * `log_${loglevel}()` - logs massage with error on error without changing the result
 /// * `log_${loglevel}_and_replace() - log message and consume error, replacing it with parameter
 /// * `log_${loglevel}_and_replace_with()` - log message and consume error but construct
-///                                          the replacement using a reference to error before
-///                                          consuming it.
+///   the replacement using a reference to error before consuming it.
 ///
 /// If you're not sure what's the usefulness of the last one (or two), here's a real-life use case:
 /// A HTTP server/application needs to log errors and also return more general responses (e.g. 404,
@@ -94,23 +224,147 @@ pub trait LogResult: IntoResult where <Self as IntoResult>::Error: 'static + std
     }
 
     fn log_error<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
-        self.with_err(|error| logger.log_error(message, error))
+        self.with_err(|error| if logger.enabled(LogLevel::Error) {
+            logger.log_error(message, error);
+        })
+    }
+
+    /// Logs the deepest source of the error as error instead of the whole chain
+    ///
+    /// Keeps logs concise when the outer errors are just noise wrapping the real cause. The result
+    /// is returned unchanged.
+    fn log_root_cause<L: Log>(self, logger: L, message: &str) -> Result<Self::Value, Self::Error> {
+        self.log_root_cause_with_level(logger, LogLevel::Error, message)
+    }
+
+    /// Same as `log_root_cause` but with explicit level
+    fn log_root_cause_with_level<L: Log>(self, mut logger: L, level: LogLevel, message: &str) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(level) {
+            level.log(&mut logger, message, error.root_cause());
+        })
+    }
+
+    /// Logs the error and then calls `then` with it
+    ///
+    /// Composes logging with additional side effects (metrics, alerts...). The result is returned
+    /// unchanged. `then` is called even if the level is disabled in the logger.
+    fn tap_err_log<L: Log, F>(self, mut logger: L, message: &str, then: F) -> Result<Self::Value, Self::Error> where F: FnOnce(&Self::Error) {
+        self.with_err(|error| {
+            if logger.enabled(LogLevel::Error) {
+                logger.log_error(message, error);
+            }
+            then(error);
+        })
+    }
+
+    /// Logs the error only if `predicate` returns `true`
+    ///
+    /// Handy for skipping expected errors (e.g. `NotFound`). The result is returned unchanged.
+    fn log_error_if<L: Log, P>(self, mut logger: L, message: &str, predicate: P) -> Result<Self::Value, Self::Error> where P: FnOnce(&Self::Error) -> bool {
+        self.with_err(|error| if logger.enabled(LogLevel::Error) && predicate(error) {
+            logger.log_error(message, error);
+        })
+    }
+
+    /// Same as `log_error` but appends ` (at file:line)` of the caller to the message
+    #[track_caller]
+    fn log_error_here<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
+        let location = std::panic::Location::caller();
+        self.with_err(|error| if logger.enabled(LogLevel::Error) {
+            logger.log_error(&format!("{} (at {}:{})", message, location.file(), location.line()), error);
+        })
     }
 
     fn log_warning<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
-        self.with_err(|error| logger.log_warning(message, error))
+        self.with_err(|error| if logger.enabled(LogLevel::Warning) {
+            logger.log_warning(message, error);
+        })
     }
 
     fn log_info<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
-        self.with_err(|error| logger.log_info(message, error))
+        self.with_err(|error| if logger.enabled(LogLevel::Info) {
+            logger.log_info(message, error);
+        })
     }
 
     fn log_debug<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
-        self.with_err(|error| logger.log_debug(message, error))
+        self.with_err(|error| if logger.enabled(LogLevel::Debug) {
+            logger.log_debug(message, error);
+        })
     }
 
     fn log_trace<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> {
-        self.with_err(|error| logger.log_trace(message, error))
+        self.with_err(|error| if logger.enabled(LogLevel::Trace) {
+            logger.log_trace(message, error);
+        })
+    }
+
+    /// Same as `log_error` but the message is given as `format_args!()`
+    ///
+    /// The message is only formatted if there is an error to log. Whether it's formatted into a
+    /// `String` depends on the logger, see `Log::log_fmt`.
+    fn log_error_fmt<L: Log>(self, mut logger: L, message: std::fmt::Arguments) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Error) {
+            logger.log_fmt(LogLevel::Error, message, error);
+        })
+    }
+
+    fn log_warning_fmt<L: Log>(self, mut logger: L, message: std::fmt::Arguments) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Warning) {
+            logger.log_fmt(LogLevel::Warning, message, error);
+        })
+    }
+
+    fn log_info_fmt<L: Log>(self, mut logger: L, message: std::fmt::Arguments) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Info) {
+            logger.log_fmt(LogLevel::Info, message, error);
+        })
+    }
+
+    fn log_debug_fmt<L: Log>(self, mut logger: L, message: std::fmt::Arguments) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Debug) {
+            logger.log_fmt(LogLevel::Debug, message, error);
+        })
+    }
+
+    fn log_trace_fmt<L: Log>(self, mut logger: L, message: std::fmt::Arguments) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Trace) {
+            logger.log_fmt(LogLevel::Trace, message, error);
+        })
+    }
+
+    /// Same as `log_error` but the message is produced by a closure
+    ///
+    /// The closure is only called if there is an error to log, so expensive messages are not built
+    /// needlessly. It may return anything that can be borrowed as `str` (`String`, `Cow<str>`...).
+    fn log_error_lazy<L: Log, M: AsRef<str>, F: FnOnce() -> M>(self, mut logger: L, message: F) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Error) {
+            logger.log_error(message().as_ref(), error);
+        })
+    }
+
+    fn log_warning_lazy<L: Log, M: AsRef<str>, F: FnOnce() -> M>(self, mut logger: L, message: F) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Warning) {
+            logger.log_warning(message().as_ref(), error);
+        })
+    }
+
+    fn log_info_lazy<L: Log, M: AsRef<str>, F: FnOnce() -> M>(self, mut logger: L, message: F) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Info) {
+            logger.log_info(message().as_ref(), error);
+        })
+    }
+
+    fn log_debug_lazy<L: Log, M: AsRef<str>, F: FnOnce() -> M>(self, mut logger: L, message: F) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Debug) {
+            logger.log_debug(message().as_ref(), error);
+        })
+    }
+
+    fn log_trace_lazy<L: Log, M: AsRef<str>, F: FnOnce() -> M>(self, mut logger: L, message: F) -> Result<Self::Value, Self::Error> {
+        self.with_err(|error| if logger.enabled(LogLevel::Trace) {
+            logger.log_trace(message().as_ref(), error);
+        })
     }
 
     fn log_error_and_replace<E, L: LogOwned>(self, logger: L, message: &str, replacement: E) -> Result<Self::Value, E> {
@@ -152,10 +406,75 @@ pub trait LogResult: IntoResult where <Self as IntoResult>::Error: 'static + std
     fn log_trace_and_replace_with<E, F, L: LogOwned>(self, mut logger: L, message: &str, convert: F) -> Result<Self::Value, E> where F: FnOnce(&Self::Error) -> E {
         self.convert_and_consume_err(convert, |error| logger.log_trace_owned(message, error))
     }
+
+    /// Logs a clone of the error using the owned path and returns the original
+    ///
+    /// Useful for loggers that only implement `LogOwned` (e.g. `slog`) when the error needs to be
+    /// handled after logging.
+    fn log_error_keep<L: LogOwned>(self, mut logger: L, message: &str) -> Result<Self::Value, Self::Error> where Self::Error: Clone {
+        self.with_err(|error| logger.log_error_owned(message, error.clone()))
+    }
+
+    /// Logs the error and converts it into `BoxedError`
+    ///
+    /// The error is logged by reference and boxed afterwards, so the logger must implement `Log`.
+    /// (Logging the owned error would consume it and there would be nothing to box.)
+    fn log_error_box<L: Log>(self, mut logger: L, message: &str) -> Result<Self::Value, BoxedError> {
+        self.log_error(&mut logger, message).map_err(BoxedError::new)
+    }
+
+    /// Logs the error and wraps it in another error
+    ///
+    /// Unlike `log_error_and_replace_with` the original error is not discarded but boxed and
+    /// passed to `wrap`, so the new error can return it from `source()`.
+    fn log_error_and_wrap<E, F, L: Log>(self, mut logger: L, message: &str, wrap: F) -> Result<Self::Value, E> where F: FnOnce(BoxedError) -> E {
+        self.internal_into_result().map_err(|error| {
+            if logger.enabled(LogLevel::Error) {
+                logger.log_error(message, &error);
+            }
+            wrap(BoxedError::new(error))
+        })
+    }
 }
 
 impl<T, E: 'static + std::error::Error> LogResult for Result<T, E> {}
 
+/// Logs the error (if any) at the given level and returns the result unchanged
+///
+/// Alternative to the `log_*` methods of `LogResult` for cases where the level is only known at
+/// run time or the method chain is inconvenient (e.g. the `Result` comes from a macro).
+pub fn log_if_err<T, E, L>(result: Result<T, E>, mut logger: L, level: LogLevel, message: &str) -> Result<T, E> where E: 'static + std::error::Error, L: Log {
+    if let Err(error) = &result {
+        if logger.enabled(level) {
+            level.log(&mut logger, message, error);
+        }
+    }
+    result
+}
+
+/// Calls `f` until it succeeds, retrying at most `attempts` times
+///
+/// Each failed attempt is logged as a warning with the given message. If all attempts fail the
+/// last error is returned. Note that `attempts` is the number of *retries*, so `0` means "try
+/// once, no retry".
+pub fn retry_logging<T, E, F, L>(attempts: usize, mut logger: L, message: &str, mut f: F) -> Result<T, E> where F: FnMut() -> Result<T, E>, E: 'static + std::error::Error, L: Log {
+    let mut remaining = attempts;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                if logger.enabled(LogLevel::Warning) {
+                    logger.log_warning(message, &error);
+                }
+                if remaining == 0 {
+                    return Err(error);
+                }
+                remaining -= 1;
+            },
+        }
+    }
+}
+
 /// Abstraction over loggers
 ///
 /// This is for loggers that have to consume errors (e.g. because of sending them to another thread).
@@ -163,23 +482,47 @@ impl<T, E: 'static + std::error::Error> LogResult for Result<T, E> {}
 /// It is recommended to implement `Log` in such case and then use `impl_log_owned!(YourLogger)`.
 /// In general, `log_${loglevel}` and `log_${loglevel}_owned` should have the same behavior when
 /// observed by a user.
+///
+/// The methods require `Self: Sized` so that `dyn Log` is possible. For dynamic dispatch over
+/// loggers that only implement this trait use `LogBoxed`.
 pub trait LogOwned {
-    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E);
-    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E);
-    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E);
-    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E);
-    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E);
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) where Self: Sized;
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) where Self: Sized;
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) where Self: Sized;
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) where Self: Sized;
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) where Self: Sized;
 }
 
 /// Abstraction over loggers
 ///
 /// This is for loggers that don't have to consume errors.
+///
+/// This trait is object-safe, `Box<dyn Log>` implements both `Log` and `LogOwned`.
 pub trait Log: LogOwned {
     fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error));
     fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error));
     fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error));
     fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error));
     fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error));
+
+    /// Returns `false` if records of the given level would be dropped
+    ///
+    /// Used to avoid building messages that wouldn't be logged anyway. Returns `true` by default.
+    fn enabled(&self, _level: LogLevel) -> bool {
+        true
+    }
+
+    /// Logs a message given as `format_args!()` at the given level
+    ///
+    /// The default implementation passes messages without arguments as they are and formats the
+    /// others into a `String`. Loggers that can handle `fmt::Arguments` directly should override it
+    /// to avoid the allocation.
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        match message.as_str() {
+            Some(message) => level.log(self, message, error),
+            None => level.log(self, &message.to_string(), error),
+        }
+    }
 }
 
 impl<T: LogOwned> LogOwned for &mut T {
@@ -204,6 +547,116 @@ impl<T: LogOwned> LogOwned for &mut T {
     }
 }
 
+impl<L: Log + ?Sized> LogOwned for Box<L> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_error(message, &error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_warning(message, &error);
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_info(message, &error);
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_debug(message, &error);
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_trace(message, &error);
+    }
+}
+
+impl<L: Log + ?Sized> Log for Box<L> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_error(message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_warning(message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_info(message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_debug(message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_trace(message, error);
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        (**self).enabled(level)
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_fmt(level, message, error);
+    }
+}
+
+/// Object-safe version of `LogOwned`
+///
+/// The errors are boxed, so that the methods don't have to be generic. This is implemented for
+/// all `LogOwned` loggers and `Box<dyn LogBoxed>` implements `LogOwned`, so it can be used for
+/// dynamic dispatch over loggers that need to consume errors. (Loggers that don't should be
+/// used as `Box<dyn Log>` instead to avoid the allocation.)
+pub trait LogBoxed {
+    fn log_error_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>);
+    fn log_warning_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>);
+    fn log_info_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>);
+    fn log_debug_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>);
+    fn log_trace_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>);
+}
+
+impl<T: LogOwned> LogBoxed for T {
+    fn log_error_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>) {
+        self.log_error_owned(message, BoxedError::from(error));
+    }
+
+    fn log_warning_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>) {
+        self.log_warning_owned(message, BoxedError::from(error));
+    }
+
+    fn log_info_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>) {
+        self.log_info_owned(message, BoxedError::from(error));
+    }
+
+    fn log_debug_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>) {
+        self.log_debug_owned(message, BoxedError::from(error));
+    }
+
+    fn log_trace_boxed(&mut self, message: &str, error: Box<dyn 'static + std::error::Error>) {
+        self.log_trace_owned(message, BoxedError::from(error));
+    }
+}
+
+impl<'a> LogOwned for Box<dyn LogBoxed + 'a> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_error_boxed(message, Box::new(error));
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_warning_boxed(message, Box::new(error));
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_info_boxed(message, Box::new(error));
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_debug_boxed(message, Box::new(error));
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (**self).log_trace_boxed(message, Box::new(error));
+    }
+}
+
 /// Implements LogOwned if you implemented Log manually
 #[macro_export]
 macro_rules! impl_log_owned {
@@ -232,6 +685,25 @@ macro_rules! impl_log_owned {
     }
 }
 
+/// Logs the error as error and returns it from the current function
+///
+/// Usage: `log_bail!(logger, "message", error)`. The error is converted using `Into`, so the error
+/// type of the function must be convertible from it. Since the error is needed after logging, the
+/// logger must implement `Log` (not just `LogOwned`).
+#[macro_export]
+macro_rules! log_bail {
+    ($logger:expr, $message:expr, $error:expr) => {
+        {
+            let mut logger = $logger;
+            let error = $error;
+            if $crate::result::Log::enabled(&logger, $crate::result::LogLevel::Error) {
+                $crate::result::Log::log_error(&mut logger, $message, &error);
+            }
+            return Err(error.into());
+        }
+    }
+}
+
 impl<T: Log> Log for &mut T {
     fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
         (*self).log_error(message, error);
@@ -249,65 +721,1274 @@ impl<T: Log> Log for &mut T {
     fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
         (*self).log_trace(message, error);
     }
-}
-
-/// Marker that uses global logger provided by `log` crate to log
-#[cfg(feature = "log")]
-#[derive(Copy, Clone)]
-pub struct GlobalLogger;
 
-
-/// Generates `{message}: {error}` with sources separated by `: `.
-#[cfg(feature = "log")]
-impl Log for GlobalLogger {
-    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
-        log::error!("{}: {}", message, error.join_sources(": "));
+    fn enabled(&self, level: LogLevel) -> bool {
+        (**self).enabled(level)
     }
 
-    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
-        log::warn!("{}: {}", message, error.join_sources(": "));
-    }
-    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
-        log::info!("{}: {}", message, error.join_sources(": "));
-    }
-    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
-        log::debug!("{}: {}", message, error.join_sources(": "));
-    }
-    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
-        log::trace!("{}: {}", message, error.join_sources(": "));
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        (**self).log_fmt(level, message, error);
     }
 }
 
-#[cfg(feature = "log")]
-impl_log_owned!(GlobalLogger);
-
-/// Uses native Error logging with `errorr` as the key.
-#[cfg(feature = "slog")]
-impl LogOwned for &slog::Logger {
+/// Locks the mutex for the duration of each call
+///
+/// If the mutex is poisoned the guard is recovered and logging proceeds - losing log records
+/// because some other thread panicked would be worse.
+impl<L: LogOwned> LogOwned for std::sync::Arc<std::sync::Mutex<L>> {
     fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
-        slog::error!(self, "{}", message; "error" => #error);
+        lock_logger(self).log_error_owned(message, error);
     }
 
     fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
-        slog::warn!(self, "{}", message; "error" => #error);
+        lock_logger(self).log_warning_owned(message, error);
     }
+
     fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
-        slog::info!(self, "{}", message; "error" => #error);
+        lock_logger(self).log_info_owned(message, error);
     }
+
     fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
-        slog::debug!(self, "{}", message; "error" => #error);
+        lock_logger(self).log_debug_owned(message, error);
     }
+
     fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
-        slog::trace!(self, "{}", message; "error" => #error);
+        lock_logger(self).log_trace_owned(message, error);
     }
 }
 
-/// Prints nice error message when returned from `main()`
-///
-/// Errors are ususally formatted using `Debug` when returned from `main()`.
-/// This sucks because `Debug` is not intended for users.
-/// This alias solves it by using special error type which formats the error using `Error` trait.
-/// It contains a `Box` so it trades one allocation for convenience (just write `?` anywhere).
-///
-/// Using this for anything else is not recommended!
-pub type MultilineTerminator = Result<(), crate::error::TerminatingError<crate::error::MultilineTerminator, crate::error::BoxedError>>;
+/// Locks the mutex for the duration of each call, recovering from poisoning
+impl<L: Log> Log for std::sync::Arc<std::sync::Mutex<L>> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_error(message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_warning(message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_info(message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_debug(message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_trace(message, error);
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        lock_logger(self).enabled(level)
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        lock_logger(self).log_fmt(level, message, error);
+    }
+}
+
+fn lock_logger<L>(logger: &std::sync::Mutex<L>) -> std::sync::MutexGuard<'_, L> {
+    logger.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Forwards each log call to both loggers in order
+///
+/// Owned errors can only be consumed once, so the first logger receives a reference (thus it must
+/// implement `Log`) and the second one receives the owned error. E.g. to log into both `log` and
+/// `slog` use `TeeLogger(GlobalLogger, &slog_logger)`.
+#[derive(Copy, Clone)]
+pub struct TeeLogger<A, B>(pub A, pub B);
+
+impl<A: Log, B: LogOwned> LogOwned for TeeLogger<A, B> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.0.log_error(message, &error);
+        self.1.log_error_owned(message, error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.0.log_warning(message, &error);
+        self.1.log_warning_owned(message, error);
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.0.log_info(message, &error);
+        self.1.log_info_owned(message, error);
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.0.log_debug(message, &error);
+        self.1.log_debug_owned(message, error);
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.0.log_trace(message, &error);
+        self.1.log_trace_owned(message, error);
+    }
+}
+
+impl<A: Log, B: Log> Log for TeeLogger<A, B> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_error(message, error);
+        self.1.log_error(message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_warning(message, error);
+        self.1.log_warning(message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_info(message, error);
+        self.1.log_info(message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_debug(message, error);
+        self.1.log_debug(message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_trace(message, error);
+        self.1.log_trace(message, error);
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.0.enabled(level) || self.1.enabled(level)
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        self.0.log_fmt(level, message, error);
+        self.1.log_fmt(level, message, error);
+    }
+}
+
+/// Severity of a log record, ordered from the least severe
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    /// Calls the method of the logger corresponding to this level
+    pub fn log<L: Log + ?Sized>(self, logger: &mut L, message: &str, error: &(dyn 'static + std::error::Error)) {
+        match self {
+            LogLevel::Trace => logger.log_trace(message, error),
+            LogLevel::Debug => logger.log_debug(message, error),
+            LogLevel::Info => logger.log_info(message, error),
+            LogLevel::Warning => logger.log_warning(message, error),
+            LogLevel::Error => logger.log_error(message, error),
+        }
+    }
+
+    /// Calls the owned method of the logger corresponding to this level
+    pub fn log_owned<L: LogOwned, E: 'static + std::error::Error>(self, logger: &mut L, message: &str, error: E) {
+        match self {
+            LogLevel::Trace => logger.log_trace_owned(message, error),
+            LogLevel::Debug => logger.log_debug_owned(message, error),
+            LogLevel::Info => logger.log_info_owned(message, error),
+            LogLevel::Warning => logger.log_warning_owned(message, error),
+            LogLevel::Error => logger.log_error_owned(message, error),
+        }
+    }
+}
+
+/// Forwards only records with level at least `min_level` to the inner logger
+///
+/// Dropped owned errors are simply discarded. Useful for adjusting verbosity without touching
+/// global log configuration.
+#[derive(Copy, Clone)]
+pub struct FilteredLogger<L> {
+    pub inner: L,
+    pub min_level: LogLevel,
+}
+
+impl<L> FilteredLogger<L> {
+    pub fn new(inner: L, min_level: LogLevel) -> Self {
+        FilteredLogger { inner, min_level }
+    }
+}
+
+impl<L: LogOwned> LogOwned for FilteredLogger<L> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        if LogLevel::Error >= self.min_level {
+            self.inner.log_error_owned(message, error);
+        }
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        if LogLevel::Warning >= self.min_level {
+            self.inner.log_warning_owned(message, error);
+        }
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        if LogLevel::Info >= self.min_level {
+            self.inner.log_info_owned(message, error);
+        }
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        if LogLevel::Debug >= self.min_level {
+            self.inner.log_debug_owned(message, error);
+        }
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        if LogLevel::Trace >= self.min_level {
+            self.inner.log_trace_owned(message, error);
+        }
+    }
+}
+
+impl<L: Log> Log for FilteredLogger<L> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        if LogLevel::Error >= self.min_level {
+            self.inner.log_error(message, error);
+        }
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        if LogLevel::Warning >= self.min_level {
+            self.inner.log_warning(message, error);
+        }
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        if LogLevel::Info >= self.min_level {
+            self.inner.log_info(message, error);
+        }
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        if LogLevel::Debug >= self.min_level {
+            self.inner.log_debug(message, error);
+        }
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        if LogLevel::Trace >= self.min_level {
+            self.inner.log_trace(message, error);
+        }
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        level >= self.min_level && self.inner.enabled(level)
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        if level >= self.min_level {
+            self.inner.log_fmt(level, message, error);
+        }
+    }
+}
+
+/// Prepends `prefix` followed by a space to every message
+///
+/// E.g. with prefix `[db]` the message `connection failed` becomes `[db] connection failed`.
+/// Note that this allocates the combined message on each call unless the message is passed as
+/// `fmt::Arguments` to a logger that doesn't allocate them (see `Log::log_fmt`).
+#[derive(Copy, Clone)]
+pub struct PrefixLogger<L> {
+    pub inner: L,
+    pub prefix: &'static str,
+}
+
+impl<L> PrefixLogger<L> {
+    pub fn new(inner: L, prefix: &'static str) -> Self {
+        PrefixLogger { inner, prefix }
+    }
+
+    fn prefixed(&self, message: &str) -> String {
+        format!("{} {}", self.prefix, message)
+    }
+}
+
+impl<L: LogOwned> LogOwned for PrefixLogger<L> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        let message = self.prefixed(message);
+        self.inner.log_error_owned(&message, error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        let message = self.prefixed(message);
+        self.inner.log_warning_owned(&message, error);
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        let message = self.prefixed(message);
+        self.inner.log_info_owned(&message, error);
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        let message = self.prefixed(message);
+        self.inner.log_debug_owned(&message, error);
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        let message = self.prefixed(message);
+        self.inner.log_trace_owned(&message, error);
+    }
+}
+
+impl<L: Log> Log for PrefixLogger<L> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        let message = self.prefixed(message);
+        self.inner.log_error(&message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        let message = self.prefixed(message);
+        self.inner.log_warning(&message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        let message = self.prefixed(message);
+        self.inner.log_info(&message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        let message = self.prefixed(message);
+        self.inner.log_debug(&message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        let message = self.prefixed(message);
+        self.inner.log_trace(&message, error);
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        self.inner.enabled(level)
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        self.inner.log_fmt(level, format_args!("{} {}", self.prefix, message), error);
+    }
+}
+
+/// Writes records into any `io::Write` as `{LEVEL} {message}: {error}` lines
+///
+/// Sources are separated by `: `. Useful for tests and simple tools that don't need `log` or
+/// `slog`. Write errors are ignored since there's nothing reasonable the caller of a logging
+/// method could do about them.
+pub struct WriteLogger<W: std::io::Write> {
+    writer: W,
+}
+
+impl<W: std::io::Write> WriteLogger<W> {
+    pub fn new(writer: W) -> Self {
+        WriteLogger { writer }
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, level: &str, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        let _ = writeln!(self.writer, "{} {}: {}", level, message, error.join_sources(": "));
+    }
+}
+
+impl<W: std::io::Write> Log for WriteLogger<W> {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.write("ERROR", format_args!("{}", message), error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.write("WARNING", format_args!("{}", message), error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.write("INFO", format_args!("{}", message), error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.write("DEBUG", format_args!("{}", message), error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.write("TRACE", format_args!("{}", message), error);
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        let level = match level {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARNING",
+            LogLevel::Error => "ERROR",
+        };
+        self.write(level, message, error);
+    }
+}
+
+impl<W: std::io::Write> LogOwned for WriteLogger<W> {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.log_error(message, &error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.log_warning(message, &error);
+    }
+
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.log_info(message, &error);
+    }
+
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.log_debug(message, &error);
+    }
+
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        self.log_trace(message, &error);
+    }
+}
+
+/// Stores log records so they can be emitted later or discarded
+///
+/// Useful e.g. for collapsing noisy retry logs - flush them if the operation eventually failed,
+/// discard them if it succeeded. Errors are stored as `ErrorSnapshot`s.
+#[derive(Default)]
+pub struct BufferingLogger {
+    records: Vec<(LogLevel, String, ErrorSnapshot)>,
+}
+
+impl BufferingLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends all buffered records to `target` in the original order
+    pub fn flush_to<L: Log>(self, mut target: L) {
+        for (level, message, error) in &self.records {
+            level.log(&mut target, message, error);
+        }
+    }
+
+    /// Drops all buffered records
+    pub fn discard(self) {}
+
+    fn push(&mut self, level: LogLevel, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.records.push((level, message.to_owned(), ErrorSnapshot::new(error)));
+    }
+}
+
+impl Log for BufferingLogger {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(LogLevel::Error, message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(LogLevel::Warning, message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(LogLevel::Info, message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(LogLevel::Debug, message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(LogLevel::Trace, message, error);
+    }
+}
+
+impl_log_owned!(BufferingLogger);
+
+/// Collects errors logged during a request and emits them as a single record
+///
+/// Errors are stored as `ErrorSnapshot`s, so they don't need to outlive the logger. The levels of
+/// individual calls are ignored - the summary is always logged as error by `finish()`.
+#[derive(Default)]
+pub struct RequestScopeLogger {
+    errors: Vec<(String, ErrorSnapshot)>,
+}
+
+impl RequestScopeLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Logs all accumulated errors as one error record
+    ///
+    /// Nothing is logged if no errors were accumulated.
+    pub fn finish<L: LogOwned>(self, mut logger: L, message: &str) {
+        if !self.errors.is_empty() {
+            logger.log_error_owned(message, ErrorSummary { errors: self.errors });
+        }
+    }
+
+    fn push(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.errors.push((message.to_owned(), ErrorSnapshot::new(error)));
+    }
+}
+
+impl Log for RequestScopeLogger {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(message, error);
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(message, error);
+    }
+
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(message, error);
+    }
+
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(message, error);
+    }
+
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        self.push(message, error);
+    }
+}
+
+impl_log_owned!(RequestScopeLogger);
+
+/// Errors accumulated by `RequestScopeLogger`
+///
+/// Displays as `N errors: message: error; message: error...` with sources separated by `: `.
+/// A single error is displayed as `1 error: message: error`.
+#[derive(Debug)]
+pub struct ErrorSummary {
+    errors: Vec<(String, ErrorSnapshot)>,
+}
+
+impl ErrorSummary {
+    /// Messages and errors in the order they were logged
+    pub fn errors(&self) -> &[(String, ErrorSnapshot)] {
+        &self.errors
+    }
+}
+
+impl std::fmt::Display for ErrorSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        crate::collect::write_error_list(f, &self.errors, |f, (message, error)| {
+            write!(f, "{}: {}", message, error.join_sources(": "))
+        })
+    }
+}
+
+impl std::error::Error for ErrorSummary {}
+
+/// Logs an error when dropped unless disarmed
+///
+/// Create it at the beginning of an operation and call `disarm()` once the operation succeeded.
+/// If the scope is exited in any other way (e.g. by `?`) the message is logged as error with
+/// `EarlyExitError`.
+///
+/// The guard is also dropped during unwinding, in which case it logs as well and the error says
+/// that the scope was exited due to panic. Nothing is logged if panics abort.
+pub struct ErrorLogGuard<'a, L: Log> {
+    logger: L,
+    message: &'a str,
+    armed: bool,
+}
+
+impl<'a, L: Log> ErrorLogGuard<'a, L> {
+    pub fn new(logger: L, message: &'a str) -> Self {
+        ErrorLogGuard {
+            logger,
+            message,
+            armed: true,
+        }
+    }
+
+    /// Consumes the guard without logging
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl<'a, L: Log> Drop for ErrorLogGuard<'a, L> {
+    fn drop(&mut self) {
+        if self.armed && self.logger.enabled(LogLevel::Error) {
+            let error = EarlyExitError { panicked: std::thread::panicking() };
+            self.logger.log_error(self.message, &error);
+        }
+    }
+}
+
+/// Logged by `ErrorLogGuard` if it wasn't disarmed
+#[derive(Debug, thiserror::Error)]
+#[error("{}", if *panicked { "the scope was exited due to panic" } else { "the scope was exited early" })]
+pub struct EarlyExitError {
+    panicked: bool,
+}
+
+impl EarlyExitError {
+    /// Returns true if the scope was exited due to panic
+    pub fn panicked(&self) -> bool {
+        self.panicked
+    }
+}
+
+/// Marker that uses global logger provided by `log` crate to log
+#[cfg(feature = "log")]
+#[derive(Copy, Clone)]
+pub struct GlobalLogger;
+
+
+/// Generates `{message}: {error}` with sources separated by `: `.
+#[cfg(feature = "log")]
+impl Log for GlobalLogger {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::error!("{}: {}", message, error.join_sources(": "));
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::warn!("{}: {}", message, error.join_sources(": "));
+    }
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::info!("{}: {}", message, error.join_sources(": "));
+    }
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::debug!("{}: {}", message, error.join_sources(": "));
+    }
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::trace!("{}: {}", message, error.join_sources(": "));
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        log::log_enabled!(level.into())
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        log::log!(level.into(), "{}: {}", message, error.join_sources(": "));
+    }
+}
+
+#[cfg(feature = "log")]
+impl_log_owned!(GlobalLogger);
+
+/// Uses global logger provided by `log` crate with an explicit target
+///
+/// `GlobalLogger` logs under the target of this crate which makes filtering confusing. This
+/// allows logging under the target of your application (or module) instead.
+#[cfg(feature = "log")]
+#[derive(Copy, Clone)]
+pub struct GlobalLoggerWithTarget {
+    pub target: &'static str,
+}
+
+/// Generates `{message}: {error}` with sources separated by `: `.
+#[cfg(feature = "log")]
+impl Log for GlobalLoggerWithTarget {
+    fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::error!(target: self.target, "{}: {}", message, error.join_sources(": "));
+    }
+
+    fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::warn!(target: self.target, "{}: {}", message, error.join_sources(": "));
+    }
+    fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::info!(target: self.target, "{}: {}", message, error.join_sources(": "));
+    }
+    fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::debug!(target: self.target, "{}: {}", message, error.join_sources(": "));
+    }
+    fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+        log::trace!(target: self.target, "{}: {}", message, error.join_sources(": "));
+    }
+
+    fn enabled(&self, level: LogLevel) -> bool {
+        log::log_enabled!(target: self.target, level.into())
+    }
+
+    fn log_fmt(&mut self, level: LogLevel, message: std::fmt::Arguments, error: &(dyn 'static + std::error::Error)) {
+        log::log!(target: self.target, level.into(), "{}: {}", message, error.join_sources(": "));
+    }
+}
+
+#[cfg(feature = "log")]
+impl_log_owned!(GlobalLoggerWithTarget);
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/// Uses native Error logging with `errorr` as the key.
+#[cfg(feature = "slog")]
+impl LogOwned for &slog::Logger {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        slog::error!(self, "{}", message; "error" => #error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        slog::warn!(self, "{}", message; "error" => #error);
+    }
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        slog::info!(self, "{}", message; "error" => #error);
+    }
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        slog::debug!(self, "{}", message; "error" => #error);
+    }
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        slog::trace!(self, "{}", message; "error" => #error);
+    }
+}
+
+/// Forwards to the `&slog::Logger` impl, so that owned (e.g. child) loggers can be passed directly.
+#[cfg(feature = "slog")]
+impl LogOwned for slog::Logger {
+    fn log_error_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (&*self).log_error_owned(message, error);
+    }
+
+    fn log_warning_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (&*self).log_warning_owned(message, error);
+    }
+    fn log_info_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (&*self).log_info_owned(message, error);
+    }
+    fn log_debug_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (&*self).log_debug_owned(message, error);
+    }
+    fn log_trace_owned<E: 'static + std::error::Error>(&mut self, message: &str, error: E) {
+        (&*self).log_trace_owned(message, error);
+    }
+}
+
+/// Prints nice error message when returned from `main()`
+///
+/// Errors are ususally formatted using `Debug` when returned from `main()`.
+/// This sucks because `Debug` is not intended for users.
+/// This alias solves it by using special error type which formats the error using `Error` trait.
+/// It contains a `Box` so it trades one allocation for convenience (just write `?` anywhere).
+///
+/// To return a value other than `()` wrap it in `error::Success`.
+///
+/// Using this for anything else is not recommended!
+pub type MultilineTerminator<T = ()> = Result<T, crate::error::TerminatingError<crate::error::MultilineTerminator, crate::error::BoxedError>>;
+
+/// Same as `MultilineTerminator` but formats the error like cargo (`error: ...`)
+///
+/// See `error::CargoStyleTerminator` for details.
+pub type CargoStyleTerminator<T = ()> = Result<T, crate::error::TerminatingError<crate::error::CargoStyleTerminator, crate::error::BoxedError>>;
+
+/// Helpers for `Option` in `main()`
+pub trait OptionExt<T> {
+    /// Turns `None` into an error displaying `message`
+    ///
+    /// Allows `option.or_terminate("missing X")?` in `main()` returning `MultilineTerminator`.
+    fn or_terminate(self, message: &str) -> MultilineTerminator<T>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    fn or_terminate(self, message: &str) -> MultilineTerminator<T> {
+        self.ok_or_else(|| crate::error::TerminatingError::new(crate::error::BoxedError::from(message)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferingLogger, FilteredLogger, IntoResult, Log, LogBoxed, LogOwned, LogResult, RequestScopeLogger, TeeLogger};
+
+    #[derive(Debug, Clone, thiserror::Error)]
+    #[error("test error {0}")]
+    struct TestError(u32);
+
+    /// Records `(level, message)` pairs
+    #[derive(Default)]
+    struct RecordingLogger {
+        records: Vec<(&'static str, String)>,
+    }
+
+    impl RecordingLogger {
+        fn record(&mut self, level: &'static str, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.records.push((level, format!("{}: {}", message, error)));
+        }
+    }
+
+    impl Log for RecordingLogger {
+        fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.record("error", message, error);
+        }
+
+        fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.record("warning", message, error);
+        }
+
+        fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.record("info", message, error);
+        }
+
+        fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.record("debug", message, error);
+        }
+
+        fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+            self.record("trace", message, error);
+        }
+    }
+
+    impl_log_owned!(RecordingLogger);
+
+    #[test]
+    fn retry_logging_succeeds_eventually() {
+        let mut logger = RecordingLogger::default();
+        let mut calls = 0;
+        let result = super::retry_logging(3, &mut logger, "attempt failed", || {
+            calls += 1;
+            if calls < 3 { Err(TestError(calls)) } else { Ok(calls) }
+        });
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(logger.records, [("warning", "attempt failed: test error 1".to_owned()), ("warning", "attempt failed: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn retry_logging_zero_attempts_tries_once() {
+        let mut logger = RecordingLogger::default();
+        let mut calls = 0;
+        let result = super::retry_logging(0, &mut logger, "attempt failed", || -> Result<(), _> {
+            calls += 1;
+            Err(TestError(calls))
+        });
+        assert_eq!(result.unwrap_err().0, 1);
+        assert_eq!(logger.records.len(), 1);
+    }
+
+    #[test]
+    fn tee_logger() {
+        let mut first = RecordingLogger::default();
+        let mut second = RecordingLogger::default();
+        let _ = Err::<(), _>(TestError(1)).log_error(TeeLogger(&mut first, &mut second), "failed");
+        let _ = Err::<(), _>(TestError(2)).log_info_and_replace(TeeLogger(&mut first, &mut second), "failed", ());
+        let expected = [("error", "failed: test error 1".to_owned()), ("info", "failed: test error 2".to_owned())];
+        assert_eq!(first.records, expected);
+        assert_eq!(second.records, expected);
+    }
+
+    #[test]
+    fn request_scope_logger() {
+        let mut scope = RequestScopeLogger::new();
+        let _ = Err::<(), _>(TestError(1)).log_warning(&mut scope, "first");
+        let _ = Err::<(), _>(TestError(2)).log_error_and_replace(&mut scope, "second", ());
+        let mut logger = RecordingLogger::default();
+        scope.finish(&mut logger, "request failed");
+        assert_eq!(logger.records, [("error", "request failed: 2 errors: first: test error 1; second: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn request_scope_logger_single_error() {
+        let mut scope = RequestScopeLogger::new();
+        let _ = Err::<(), _>(TestError(1)).log_warning(&mut scope, "first");
+        let mut logger = RecordingLogger::default();
+        scope.finish(&mut logger, "request failed");
+        assert_eq!(logger.records, [("error", "request failed: 1 error: first: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn log_error_and_wrap() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("wrapped")]
+        struct Wrapped(#[source] crate::error::BoxedError);
+
+        let mut logger = RecordingLogger::default();
+        let error = Err::<(), _>(TestError(1)).log_error_and_wrap(&mut logger, "failed", Wrapped).unwrap_err();
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "test error 1");
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn log_error_keep() {
+        let mut logger = RecordingLogger::default();
+        let error = Err::<(), _>(TestError(1)).log_error_keep(&mut logger, "failed").unwrap_err();
+        assert_eq!(error.0, 1);
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn log_error_here() {
+        let mut logger = RecordingLogger::default();
+        let line = line!() + 1;
+        let _ = Err::<(), _>(TestError(1)).log_error_here(&mut logger, "failed");
+        assert_eq!(logger.records, [("error", format!("failed (at {}:{}): test error 1", file!(), line))]);
+    }
+
+    #[test]
+    fn log_fmt() {
+        let mut logger = RecordingLogger::default();
+        for i in 0..2 {
+            let _ = Err::<(), _>(TestError(i)).log_info_fmt(&mut logger, format_args!("processing item {}", i));
+        }
+        assert_eq!(logger.records, [("info", "processing item 0: test error 0".to_owned()), ("info", "processing item 1: test error 1".to_owned())]);
+
+        // the arguments are passed through the wrappers and written directly
+        let writer = super::WriteLogger::new(Vec::new());
+        let mut logger = super::PrefixLogger::new(FilteredLogger::new(writer, super::LogLevel::Info), "[db]");
+        let _ = Err::<(), _>(TestError(1)).log_info_fmt(&mut logger, format_args!("item {}", 1));
+        let _ = Err::<(), _>(TestError(2)).log_debug_fmt(&mut logger, format_args!("item {}", 2));
+        assert_eq!(String::from_utf8(logger.inner.inner.into_inner()).unwrap(), "INFO [db] item 1: test error 1\n");
+    }
+
+    #[test]
+    fn map_err_boxed() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("outer")]
+        struct Outer(#[source] TestError);
+
+        let error = Err::<(), _>(Outer(TestError(1))).map_err_boxed().unwrap_err();
+        assert_eq!(error.to_string(), "outer");
+        assert_eq!(std::error::Error::source(&error).unwrap().to_string(), "test error 1");
+    }
+
+    #[test]
+    fn log_bail() {
+        fn fails(logger: &mut RecordingLogger) -> Result<(), Box<dyn std::error::Error>> {
+            log_bail!(logger, "failed", TestError(1));
+        }
+
+        let mut logger = RecordingLogger::default();
+        assert_eq!(fails(&mut logger).unwrap_err().to_string(), "test error 1");
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn log_error_if() {
+        let mut logger = RecordingLogger::default();
+        let _ = Err::<(), _>(TestError(1)).log_error_if(&mut logger, "failed", |error| error.0 != 1);
+        assert!(logger.records.is_empty());
+        let error = Err::<(), _>(TestError(2)).log_error_if(&mut logger, "failed", |error| error.0 != 1).unwrap_err();
+        assert_eq!(error.0, 2);
+        assert_eq!(logger.records, [("error", "failed: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn buffering_logger() {
+        let mut buffer = BufferingLogger::new();
+        let _ = Err::<(), _>(TestError(1)).log_warning(&mut buffer, "first");
+        let _ = Err::<(), _>(TestError(2)).log_debug_and_replace(&mut buffer, "second", ());
+        let mut logger = RecordingLogger::default();
+        buffer.flush_to(&mut logger);
+        assert_eq!(logger.records, [("warning", "first: test error 1".to_owned()), ("debug", "second: test error 2".to_owned())]);
+
+        let mut buffer = BufferingLogger::new();
+        let _ = Err::<(), _>(TestError(1)).log_warning(&mut buffer, "first");
+        buffer.discard();
+    }
+
+    #[test]
+    fn dynamic_loggers() {
+        let mut recording = RecordingLogger::default();
+        {
+            let mut logger: Box<dyn Log + '_> = Box::new(&mut recording);
+            let _ = Err::<(), _>(TestError(1)).log_error(&mut logger, "failed");
+            let _ = Err::<(), _>(TestError(2)).log_info_and_replace(&mut logger, "failed", ());
+        }
+        {
+            let mut logger: Box<dyn LogBoxed + '_> = Box::new(&mut recording);
+            let _ = Err::<(), _>(TestError(3)).log_warning_and_replace(&mut logger, "failed", ());
+        }
+        assert_eq!(recording.records, [("error", "failed: test error 1".to_owned()), ("info", "failed: test error 2".to_owned()), ("warning", "failed: test error 3".to_owned())]);
+    }
+
+    #[test]
+    fn log_error_box() {
+        let mut logger = RecordingLogger::default();
+        let error = Err::<(), _>(TestError(1)).log_error_box(&mut logger, "failed").unwrap_err();
+        assert!(error.downcast_ref::<TestError>().is_some());
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn filtered_logger() {
+        let mut recorder = RecordingLogger::default();
+        {
+            let mut logger = FilteredLogger::new(&mut recorder, super::LogLevel::Warning);
+            logger.log_info("info", &TestError(1));
+            logger.log_warning("warning", &TestError(2));
+            logger.log_debug_owned("debug", TestError(3));
+            logger.log_error_owned("error", TestError(4));
+        }
+        assert_eq!(recorder.records, [("warning", "warning: test error 2".to_owned()), ("error", "error: test error 4".to_owned())]);
+    }
+
+    #[test]
+    fn log_if_err() {
+        let mut logger = RecordingLogger::default();
+        let ok: Result<u32, TestError> = Ok(1);
+        assert_eq!(super::log_if_err(ok, &mut logger, super::LogLevel::Info, "ok").unwrap(), 1);
+        let err: Result<u32, TestError> = Err(TestError(2));
+        assert!(super::log_if_err(err, &mut logger, super::LogLevel::Info, "failed").is_err());
+        assert_eq!(logger.records, [("info", "failed: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("oops {}", 42)).unwrap_err();
+        assert_eq!(super::panic_message(&*payload), "oops 42");
+        let payload = std::panic::catch_unwind(|| panic!("oops")).unwrap_err();
+        assert_eq!(super::panic_message(&*payload), "oops");
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(42)).unwrap_err();
+        assert_eq!(super::panic_message(&*payload), "unknown panic payload");
+        assert_eq!(super::catch_unwind_or_exit(|| 42), 42);
+    }
+
+    #[test]
+    fn error_log_guard() {
+        use super::ErrorLogGuard;
+
+        fn operation(logger: &mut RecordingLogger, fail: bool) -> Result<(), TestError> {
+            let guard = ErrorLogGuard::new(logger, "operation failed");
+            if fail {
+                return Err(TestError(1));
+            }
+            guard.disarm();
+            Ok(())
+        }
+
+        let mut logger = RecordingLogger::default();
+        operation(&mut logger, false).unwrap();
+        assert!(logger.records.is_empty());
+        operation(&mut logger, true).unwrap_err();
+        assert_eq!(logger.records, [("error", "operation failed: the scope was exited early".to_owned())]);
+    }
+
+    #[test]
+    fn or_terminate() {
+        use super::OptionExt;
+
+        fn run(value: Option<u32>) -> super::MultilineTerminator<u32> {
+            let value = value.or_terminate("missing value")?;
+            Ok(value + 1)
+        }
+
+        assert_eq!(run(Some(1)).unwrap(), 2);
+        assert!(format!("{:?}", run(None).unwrap_err()).contains("failed: missing value"));
+    }
+
+    #[test]
+    fn log_lazy() {
+        let mut logger = RecordingLogger::default();
+        let ok: Result<u32, TestError> = Ok(1);
+        ok.log_error_lazy(&mut logger, || -> String { panic!("message built on Ok") }).unwrap();
+        let err: Result<u32, TestError> = Err(TestError(2));
+        err.log_info_lazy(&mut logger, || format!("attempt {}", 3)).unwrap_err();
+        assert_eq!(logger.records, [("info", "attempt 3: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn disabled_level_skips_message() {
+        use super::LogLevel;
+
+        struct ErrorsOnly(RecordingLogger);
+
+        impl Log for ErrorsOnly {
+            fn log_error(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+                self.0.log_error(message, error);
+            }
+
+            fn log_warning(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+                self.0.log_warning(message, error);
+            }
+
+            fn log_info(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+                self.0.log_info(message, error);
+            }
+
+            fn log_debug(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+                self.0.log_debug(message, error);
+            }
+
+            fn log_trace(&mut self, message: &str, error: &(dyn 'static + std::error::Error)) {
+                self.0.log_trace(message, error);
+            }
+
+            fn enabled(&self, level: LogLevel) -> bool {
+                level == LogLevel::Error
+            }
+        }
+
+        impl_log_owned!(ErrorsOnly);
+
+        let mut logger = ErrorsOnly(RecordingLogger::default());
+        let err: Result<u32, TestError> = Err(TestError(1));
+        let err = err.log_debug_lazy(&mut logger, || -> String { panic!("message built for disabled level") });
+        err.log_error(&mut logger, "failed").unwrap_err();
+        assert_eq!(logger.0.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn disabled_logger_is_not_called() {
+        use super::LogLevel;
+
+        struct Disabled;
+
+        impl Log for Disabled {
+            fn log_error(&mut self, _message: &str, _error: &(dyn 'static + std::error::Error)) {
+                panic!("logged disabled error");
+            }
+
+            fn log_warning(&mut self, _message: &str, _error: &(dyn 'static + std::error::Error)) {
+                panic!("logged disabled warning");
+            }
+
+            fn log_info(&mut self, _message: &str, _error: &(dyn 'static + std::error::Error)) {
+                panic!("logged disabled info");
+            }
+
+            fn log_debug(&mut self, _message: &str, _error: &(dyn 'static + std::error::Error)) {
+                panic!("logged disabled debug");
+            }
+
+            fn log_trace(&mut self, _message: &str, _error: &(dyn 'static + std::error::Error)) {
+                panic!("logged disabled trace");
+            }
+
+            fn enabled(&self, _level: LogLevel) -> bool {
+                false
+            }
+        }
+
+        impl_log_owned!(Disabled);
+
+        fn bail() -> Result<(), TestError> {
+            log_bail!(Disabled, "failed", TestError(1))
+        }
+
+        let error = Err::<(), _>(TestError(1)).log_error_and_wrap(Disabled, "failed", |error| error).unwrap_err();
+        assert_eq!(error.to_string(), "test error 1");
+        assert!(super::retry_logging(1, Disabled, "failed", || Err::<(), _>(TestError(1))).is_err());
+        assert!(bail().is_err());
+        drop(super::ErrorLogGuard::new(Disabled, "failed"));
+    }
+
+    #[cfg(feature = "slog")]
+    #[test]
+    fn owned_slog_logger() {
+        use std::sync::{Arc, Mutex};
+
+        /// Keeps the last key-value pair as `key=value`
+        struct LastValue(String);
+
+        impl slog::Serializer for LastValue {
+            fn emit_arguments(&mut self, key: slog::Key, val: &std::fmt::Arguments) -> slog::Result {
+                self.0 = format!("{}={}", key, val);
+                Ok(())
+            }
+        }
+
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl slog::Drain for Recorder {
+            type Ok = ();
+            type Err = slog::Never;
+
+            fn log(&self, record: &slog::Record, _values: &slog::OwnedKVList) -> Result<(), slog::Never> {
+                let mut value = LastValue(String::new());
+                slog::KV::serialize(&record.kv(), record, &mut value).unwrap();
+                self.0.lock().unwrap().push(format!("{} {}: {}", record.level().as_short_str(), record.msg(), value.0));
+                Ok(())
+            }
+        }
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let logger = slog::Logger::root(Recorder(Arc::clone(&records)), slog::o!());
+        let _ = Err::<(), _>(TestError(1)).log_error_and_replace(&logger, "failed", ());
+        let _ = Err::<(), _>(TestError(2)).log_error_and_replace(logger.new(slog::o!()), "failed", ());
+        let _ = Err::<(), _>(TestError(3)).log_warning_and_replace(logger, "retrying", ());
+        assert_eq!(*records.lock().unwrap(), ["ERRO failed: error=test error 1", "ERRO failed: error=test error 2", "WARN retrying: error=test error 3"]);
+    }
+
+    #[test]
+    fn exit_code_from() {
+        use std::process::ExitCode;
+
+        assert_eq!(super::exit_code_from(Ok::<_, TestError>(42)), ExitCode::SUCCESS);
+        assert_eq!(super::exit_code_from(Err::<(), _>(TestError(1))), ExitCode::from(2));
+    }
+
+    #[test]
+    fn write_logger() {
+        let mut logger = super::WriteLogger::new(Vec::new());
+        logger.log_warning("retrying", &TestError(1));
+        logger.log_error_owned("failed", TestError(2));
+        assert_eq!(String::from_utf8(logger.into_inner()).unwrap(), "WARNING retrying: test error 1\nERROR failed: test error 2\n");
+    }
+
+    #[test]
+    fn context() {
+        use crate::error::DisplayError;
+
+        let result: Result<(), TestError> = Err(TestError(1));
+        let error = result.context("loading config").unwrap_err();
+        assert_eq!(error.join_sources(": ").to_string(), "loading config: test error 1");
+        assert!(error.find_source::<TestError>().is_some());
+    }
+
+    #[test]
+    fn shared_logger() {
+        use std::sync::{Arc, Mutex};
+
+        let logger = Arc::new(Mutex::new(RecordingLogger::default()));
+        let threads = (0..4)
+            .map(|i| {
+                let logger = Arc::clone(&logger);
+                std::thread::spawn(move || {
+                    let result: Result<(), TestError> = Err(TestError(i));
+                    result.log_error(logger, "failed").unwrap_err();
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let mut records = logger.lock().unwrap().records.clone();
+        records.sort();
+        let expected = (0..4).map(|i| ("error", format!("failed: test error {}", i))).collect::<Vec<_>>();
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn prefix_logger() {
+        let mut recorder = RecordingLogger::default();
+        let mut logger = super::PrefixLogger::new(&mut recorder, "[db]");
+        logger.log_warning("connection failed", &TestError(1));
+        logger.log_error_owned("giving up", TestError(2));
+        assert_eq!(recorder.records, [("warning", "[db] connection failed: test error 1".to_owned()), ("error", "[db] giving up: test error 2".to_owned())]);
+    }
+
+    #[test]
+    fn tap_err_log() {
+        let mut logger = RecordingLogger::default();
+        let mut failures = 0;
+        let err: Result<u32, TestError> = Err(TestError(1));
+        err.tap_err_log(&mut logger, "failed", |_| failures += 1).unwrap_err();
+        let ok: Result<u32, TestError> = Ok(1);
+        ok.tap_err_log(&mut logger, "failed", |_| failures += 1).unwrap();
+        assert_eq!(failures, 1);
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned())]);
+    }
+
+    #[test]
+    fn log_root_cause() {
+        use crate::error::ContextError;
+
+        let mut logger = RecordingLogger::default();
+        let err: Result<u32, _> = Err(ContextError::new("loading config", TestError(1)));
+        err.log_root_cause(&mut logger, "failed").unwrap_err();
+        let err: Result<u32, _> = Err(TestError(2));
+        err.log_root_cause_with_level(&mut logger, super::LogLevel::Info, "failed").unwrap_err();
+        assert_eq!(logger.records, [("error", "failed: test error 1".to_owned()), ("info", "failed: test error 2".to_owned())]);
+    }
+}